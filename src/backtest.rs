@@ -0,0 +1,422 @@
+use crate::forecast::{forecast_dividends_with_rates, ForecastParams, Reinvestment};
+use crate::growthmodel::{annual_dividends_through, GrowthModel};
+use chrono::{Datelike, Duration, NaiveDate};
+use polars::prelude::*;
+use std::collections::BTreeMap;
+
+/// Closing share prices of companies loaded from CSV file with "Symbol", "Date" and "Close"
+/// columns
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriceHistory {
+    closes: BTreeMap<String, Vec<(NaiveDate, f64)>>,
+}
+
+impl PriceHistory {
+    pub fn from_closes(closes: &[(&str, NaiveDate, f64)]) -> Self {
+        let mut history = PriceHistory::default();
+        closes.iter().for_each(|(symbol, date, close)| {
+            history
+                .closes
+                .entry(symbol.to_string())
+                .or_default()
+                .push((*date, *close))
+        });
+        history
+            .closes
+            .values_mut()
+            .for_each(|closes| closes.sort_by(|a, b| a.0.cmp(&b.0)));
+        history
+    }
+
+    pub fn from_csv(path: &str) -> Result<Self, &'static str> {
+        let df = CsvReader::from_path(path)
+            .map_err(|e| {
+                log::error!("Unable to open {path}: {e}");
+                "Error: Unable to open prices file"
+            })?
+            .has_header(true)
+            .finish()
+            .map_err(|e| {
+                log::error!("Unable to read {path}: {e}");
+                "Error: Unable to read prices file"
+            })?;
+        let text = |name: &str| -> Result<Vec<Option<String>>, &'static str> {
+            Ok(df
+                .column(name)
+                .map_err(|_| "Error: Prices file should have Symbol, Date and Close columns")?
+                .cast(&DataType::Utf8)
+                .map_err(|_| "Error: Symbol and Date columns of prices file should be text")?
+                .utf8()
+                .map_err(|_| "Error: Symbol and Date columns of prices file should be text")?
+                .into_iter()
+                .map(|v| v.map(|v| v.to_owned()))
+                .collect())
+        };
+        let symbols = text("Symbol")?;
+        let dates = text("Date")?;
+        let closes = df
+            .column("Close")
+            .and_then(|c| c.cast(&DataType::Float64))
+            .map_err(|_| "Error: Close prices are not numbers")?;
+        let closes: Vec<(&str, NaiveDate, f64)> = symbols
+            .iter()
+            .zip(&dates)
+            .zip(
+                closes
+                    .f64()
+                    .map_err(|_| "Error: Close prices are not numbers")?,
+            )
+            .filter_map(|((symbol, date), close)| {
+                let date = NaiveDate::parse_from_str(date.as_ref()?, "%Y-%m-%d").ok()?;
+                Some((symbol.as_deref()?, date, close?))
+            })
+            .collect();
+        Ok(PriceHistory::from_closes(&closes))
+    }
+
+    /// Last close of a company in every year of history, except the last year which may be
+    /// partial
+    pub fn year_end_closes(&self, symbol: &str) -> Vec<(i32, f64)> {
+        let mut year_ends: Vec<(i32, f64)> = vec![];
+        self.closes
+            .get(symbol)
+            .into_iter()
+            .flatten()
+            .for_each(|(date, close)| match year_ends.last_mut() {
+                Some((year, last)) if *year == date.year() => *last = *close,
+                _ => year_ends.push((date.year(), *close)),
+            });
+        year_ends.pop();
+        year_ends
+    }
+
+    /// Whether price history of a company reaches given date
+    pub fn covers(&self, symbol: &str, date: NaiveDate) -> bool {
+        self.closes
+            .get(symbol)
+            .and_then(|closes| closes.last())
+            .map_or(false, |(last, _)| *last >= date)
+    }
+
+    /// Most recent close of a company on or before given date
+    pub fn close(&self, symbol: &str, date: NaiveDate) -> Option<f64> {
+        self.closes
+            .get(symbol)?
+            .iter()
+            .rev()
+            .find(|(d, _)| *d <= date)
+            .map(|(_, close)| *close)
+    }
+}
+
+/// Dividends paid after start and on or before end date
+pub fn dividends_between(div_history: &[(String, f64)], start: NaiveDate, end: NaiveDate) -> f64 {
+    div_history
+        .iter()
+        .filter_map(|(date, amount)| {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            (date > start && date <= end).then_some(*amount)
+        })
+        .sum()
+}
+
+/// Dividend history known at given date
+pub fn history_until(div_history: &[(String, f64)], as_of: NaiveDate) -> Vec<(String, f64)> {
+    div_history
+        .iter()
+        .filter(|(date, _)| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_or(false, |date| date <= as_of)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Projection of a single year after as of date compared with what actually happened
+#[derive(Debug, Clone, PartialEq)]
+pub struct HorizonError {
+    /// Number of years after as of date
+    pub year: u32,
+    /// Dividends per share paid within the year
+    pub projected_div: f64,
+    pub actual_div: f64,
+    /// Relative error of projected dividends[%]
+    pub div_error: Option<f64>,
+    /// Share price at the end of the year
+    pub projected_price: f64,
+    pub actual_price: f64,
+    /// Relative error of projected share price[%]
+    pub price_error: Option<f64>,
+}
+
+/// Forecast of a company made with data known at as of date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backtest {
+    pub symbol: String,
+    pub as_of: NaiveDate,
+    /// Dividends per share paid within a year before as of date
+    pub trailing_div: f64,
+    /// Average dividend growth rate of (up to) five years to the last year completed by as of
+    /// date[%]
+    pub dgr: f64,
+    /// Errors of horizons which already elapsed
+    pub errors: Vec<HorizonError>,
+}
+
+fn relative_error(projected: f64, actual: f64) -> Option<f64> {
+    (actual != 0.0).then(|| (projected - actual) / actual * 100.0)
+}
+
+/// Forecast dividends per share and share price of a company using only dividend history and
+/// price known at as of date. Projections are compared with actual dividends and prices of every
+/// year elapsed until today (at most given number of years). Price history has to cover as of
+/// date and the end of every elapsed year
+#[allow(clippy::too_many_arguments)]
+pub fn backtest(
+    symbol: &str,
+    div_history: &[(String, f64)],
+    prices: &PriceHistory,
+    as_of: NaiveDate,
+    today: NaiveDate,
+    years: u32,
+    share_price_growth_rate: f64,
+    growth_model: &GrowthModel,
+) -> Result<Backtest, &'static str> {
+    let known_history = history_until(div_history, as_of);
+    let trailing_div = dividends_between(&known_history, as_of - Duration::days(365), as_of);
+    let num_capitalizations = known_history
+        .iter()
+        .filter(|(date, _)| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_or(false, |date| date > as_of - Duration::days(365))
+        })
+        .count() as u32;
+    if trailing_div <= 0.0 || num_capitalizations == 0 {
+        log::error!("{symbol}: no dividends paid within a year before {as_of}");
+        return Err("Error: No dividends known at as of date to backtest");
+    }
+
+    // Five years DGR as known at as of date, year of as of date counts only when it is complete
+    let last_complete_year = if as_of.month() == 12 && as_of.day() == 31 {
+        as_of.year()
+    } else {
+        as_of.year() - 1
+    };
+    let annual = annual_dividends_through(&known_history, last_complete_year);
+    let recent = &annual[annual.len().saturating_sub(6)..];
+    let dgr = crate::growth::cagr(recent).ok_or_else(|| {
+        log::error!("{symbol}: not enough dividend history before {as_of} to compute DGR");
+        "Error: Not enough dividend history to backtest"
+    })?;
+
+    let elapsed_years = ((today - as_of).num_days() / 365).clamp(0, years as i64) as u32;
+    let share_price = prices.close(symbol, as_of).ok_or_else(|| {
+        log::error!("{symbol}: no closing price on or before {as_of}");
+        "Error: No share price of company at as of date to backtest"
+    })?;
+    // Single share is held, so forecast gains are dividends per share
+    let params = ForecastParams {
+        base_capital: share_price,
+        div_yield: trailing_div / share_price,
+        div_growth: dgr / 100.0,
+        share_price,
+        share_price_growth_rate,
+        tax_rate: 0.0,
+        num_capitalizations,
+        reinvestment: Reinvestment::None,
+        contributions: None,
+    };
+    let time_line: Vec<u32> = (1..365 * elapsed_years + 1).collect();
+    let forecast = forecast_dividends_with_rates(
+        &params,
+        &time_line,
+        &growth_model.annual_rates(&params, &known_history, elapsed_years),
+    );
+
+    let errors = (1..=elapsed_years)
+        .map(|year| {
+            let end = (365 * year) as usize - 1;
+            let previous_gains = if year == 1 {
+                0.0
+            } else {
+                forecast.gains[end - 365]
+            };
+            let projected_div = forecast.gains[end] - previous_gains;
+            let actual_div = dividends_between(
+                div_history,
+                as_of + Duration::days(365 * (year as i64 - 1)),
+                as_of + Duration::days(365 * year as i64),
+            );
+            let projected_price = forecast.value[end];
+            let end_date = as_of + Duration::days(365 * year as i64);
+            let actual_price = prices
+                .close(symbol, end_date)
+                .filter(|_| prices.covers(symbol, end_date))
+                .ok_or_else(|| {
+                    log::error!("{symbol}: no closing price of {end_date} in price history");
+                    "Error: Price history does not cover backtested years"
+                })?;
+            Ok(HorizonError {
+                year,
+                projected_div,
+                actual_div,
+                div_error: relative_error(projected_div, actual_div),
+                projected_price,
+                actual_price,
+                price_error: relative_error(projected_price, actual_price),
+            })
+        })
+        .collect::<Result<Vec<HorizonError>, &'static str>>()?;
+
+    Ok(Backtest {
+        symbol: symbol.to_owned(),
+        as_of,
+        trailing_div,
+        dgr,
+        errors,
+    })
+}
+
+/// Mean absolute error[%] of dividends and share price projections by horizon year across
+/// companies
+pub fn mean_absolute_errors(backtests: &[Backtest]) -> Vec<(u32, Option<f64>, Option<f64>)> {
+    let mean = |errors: Vec<f64>| {
+        (!errors.is_empty())
+            .then(|| errors.iter().map(|e| e.abs()).sum::<f64>() / errors.len() as f64)
+    };
+    let max_year = backtests
+        .iter()
+        .map(|b| b.errors.len() as u32)
+        .max()
+        .unwrap_or(0);
+    (1..=max_year)
+        .map(|year| {
+            let horizon: Vec<&HorizonError> = backtests
+                .iter()
+                .filter_map(|b| b.errors.iter().find(|e| e.year == year))
+                .collect();
+            (
+                year,
+                mean(horizon.iter().filter_map(|e| e.div_error).collect()),
+                mean(horizon.iter().filter_map(|e| e.price_error).collect()),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// Quarterly dividends of 0.25$ per share in 2015 growing 10% a year, paid since 2014
+    fn div_history() -> Vec<(String, f64)> {
+        (2014..2024)
+            .flat_map(|year| {
+                let amount = 0.25 * 1.1f64.powi(year - 2015);
+                [2, 5, 8, 11]
+                    .iter()
+                    .map(move |month| (format!("{year}-{month:02}-15"), amount))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_price_history() -> Result<(), String> {
+        let prices = PriceHistory::from_closes(&[
+            ("ABM", date(2020, 1, 3), 40.0),
+            ("ABM", date(2020, 1, 2), 39.0),
+            ("XOM", date(2020, 1, 2), 70.0),
+        ]);
+        assert_eq!(prices.close("ABM", date(2020, 1, 5)), Some(40.0));
+        assert_eq!(prices.close("ABM", date(2020, 1, 2)), Some(39.0));
+        assert_eq!(prices.close("ABM", date(2019, 12, 31)), None);
+        assert_eq!(prices.close("KO", date(2020, 1, 5)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_backtest() -> Result<(), String> {
+        let prices = PriceHistory::from_closes(&[
+            ("ABM", date(2021, 1, 1), 100.0),
+            ("ABM", date(2022, 1, 1), 110.0),
+            ("ABM", date(2023, 1, 1), 100.0),
+        ]);
+        let result = backtest(
+            "ABM",
+            &div_history(),
+            &prices,
+            date(2021, 1, 1),
+            date(2023, 6, 1),
+            5,
+            0.1,
+            &GrowthModel::Constant,
+        )?;
+        // Dividends of 2020 and DGR of 2015-2020 (first year of history is skipped as partial)
+        assert_eq!((result.trailing_div * 1e6).round() / 1e6, 1.61051);
+        assert_eq!((result.dgr * 1e6).round() / 1e6, 10.0);
+        // Two years elapsed until today
+        assert_eq!(result.errors.len(), 2);
+        let first = &result.errors[0];
+        assert_eq!((first.projected_div * 1e6).round() / 1e6, 1.61051);
+        assert_eq!((first.actual_div * 1e6).round() / 1e6, 1.771561);
+        assert_eq!(first.div_error.map(|e| e.round()), Some(-9.0));
+        assert_eq!(first.price_error.map(|e| e.round()), Some(0.0));
+        let second = &result.errors[1];
+        assert_eq!(second.price_error.map(|e| e.round()), Some(21.0));
+
+        let errors = mean_absolute_errors(&[result]);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].1.map(|e| e.round()), Some(9.0));
+
+        // Year completed just before as of date counts in DGR: doubled dividends of 2020
+        let doubled: Vec<(String, f64)> = div_history()
+            .into_iter()
+            .map(|(d, a)| {
+                if d.starts_with("2020") {
+                    (d, 2.0 * a)
+                } else {
+                    (d, a)
+                }
+            })
+            .collect();
+        let result = backtest(
+            "ABM",
+            &doubled,
+            &prices,
+            date(2021, 1, 1),
+            date(2023, 6, 1),
+            5,
+            0.1,
+            &GrowthModel::Constant,
+        )?;
+        // (2 * 1.1^5)^(1/5) - 1
+        assert_eq!(result.dgr.round(), 26.0);
+
+        let backtest_of = |symbol: &str, prices: &PriceHistory, as_of: NaiveDate| {
+            backtest(
+                symbol,
+                &div_history(),
+                prices,
+                as_of,
+                date(2023, 6, 1),
+                5,
+                0.1,
+                &GrowthModel::Constant,
+            )
+        };
+        // No dividends known at as of date
+        assert!(backtest_of("ABM", &prices, date(2014, 1, 1)).is_err());
+        // No price at as of date
+        assert!(backtest_of("XOM", &prices, date(2021, 1, 1)).is_err());
+        // Price history ends before second elapsed year
+        let short_prices = PriceHistory::from_closes(&[
+            ("ABM", date(2021, 1, 1), 100.0),
+            ("ABM", date(2022, 1, 1), 110.0),
+        ]);
+        assert!(backtest_of("ABM", &short_prices, date(2021, 1, 1)).is_err());
+        Ok(())
+    }
+}
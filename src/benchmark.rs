@@ -0,0 +1,191 @@
+use crate::screening::optional_f64_column;
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use polars::prelude::*;
+use std::fmt;
+
+/// Dividend yield of a market benchmark (e.g. S&P 500) and what it was derived from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Benchmark {
+    /// Div Yield[%]
+    pub div_yield: f64,
+    pub as_of: NaiveDate,
+    pub source: String,
+}
+
+impl fmt::Display for Benchmark {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.2}% ({}, as of {})",
+            self.div_yield, self.source, self.as_of
+        )
+    }
+}
+
+/// Dividends paid within a year before given date relative to share price[%]
+pub fn trailing_div_yield(
+    div_history: &[(String, f64)],
+    share_price: f64,
+    as_of: NaiveDate,
+) -> Option<f64> {
+    if share_price <= 0.0 {
+        return None;
+    }
+    let start = as_of - Duration::days(365);
+    let paid: Vec<f64> = div_history
+        .iter()
+        .filter_map(|(date, amount)| {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+            (date > start && date <= as_of).then_some(*amount)
+        })
+        .collect();
+    if paid.is_empty() {
+        None
+    } else {
+        Some(paid.iter().sum::<f64>() / share_price * 100.0)
+    }
+}
+
+/// Weighted average of Div Yields. Constituents with no weight or yield are skipped
+pub fn weighted_div_yield(weights: &[Option<f64>], yields: &[Option<f64>]) -> Option<f64> {
+    let (total_weight, weighted_sum) = weights
+        .iter()
+        .zip(yields)
+        .filter_map(|(weight, div_yield)| Some(((*weight)?, (*div_yield)?)))
+        .fold((0.0, 0.0), |(total, sum), (weight, div_yield)| {
+            (total + weight, sum + weight * div_yield)
+        });
+    (total_weight > 0.0).then(|| weighted_sum / total_weight)
+}
+
+/// Benchmark Div Yield from trailing dividends and previous close of index ETF e.g. SPY
+pub fn benchmark_from_polygon(ticker: &str) -> Result<Benchmark, &'static str> {
+    let data = crate::get_polygon_data(ticker)?;
+    let as_of = Utc::now().date_naive();
+    let div_yield =
+        trailing_div_yield(&data.div_history, data.share_price, as_of).ok_or_else(|| {
+            log::error!("{ticker}: no dividends or share price to derive benchmark Div Yield");
+            "Error: Unable to derive benchmark Div Yield"
+        })?;
+    Ok(Benchmark {
+        div_yield,
+        as_of,
+        source: format!("{ticker} trailing 12 months dividends to previous close"),
+    })
+}
+
+/// Benchmark Div Yield as weighted average of Div Yields of index constituents. CSV file
+/// should have "Symbol", "Weight" and "Div Yield"[%] columns. As of date is taken from
+/// optional "Date" column (most recent one) or from modification time of the file
+pub fn benchmark_from_constituents(path: &str) -> Result<Benchmark, &'static str> {
+    let df = CsvReader::from_path(path)
+        .map_err(|e| {
+            log::error!("Unable to open {path}: {e}");
+            "Error: Unable to open constituents file"
+        })?
+        .has_header(true)
+        .finish()
+        .map_err(|e| {
+            log::error!("Unable to read {path}: {e}");
+            "Error: Unable to read constituents file"
+        })?;
+
+    let weights = optional_f64_column(&df, "Weight");
+    let yields = optional_f64_column(&df, "Div Yield");
+    let skipped = weights
+        .iter()
+        .zip(&yields)
+        .filter(|(w, y)| w.is_none() || y.is_none())
+        .count();
+    if skipped > 0 {
+        log::warn!("{skipped} constituents with no weight or Div Yield are skipped");
+    }
+    let div_yield = weighted_div_yield(&weights, &yields).ok_or_else(|| {
+        log::error!("No constituents with weight and Div Yield in {path}");
+        "Error: Unable to derive benchmark Div Yield"
+    })?;
+
+    let dates: Option<NaiveDate> = df
+        .column("Date")
+        .ok()
+        .and_then(|c| c.utf8().ok().cloned())
+        .and_then(|dates| {
+            dates
+                .into_iter()
+                .flatten()
+                .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+                .max()
+        });
+    let as_of = match dates {
+        Some(date) => date,
+        None => std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map(|modified| DateTime::<Local>::from(modified).date_naive())
+            .map_err(|e| {
+                log::error!("Unable to get modification time of {path}: {e}");
+                "Error: Unable to get as of date of constituents file"
+            })?,
+    };
+
+    Ok(Benchmark {
+        div_yield,
+        as_of,
+        source: format!(
+            "weighted average of {} constituents from {path}",
+            df.height() - skipped
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailing_div_yield() -> Result<(), String> {
+        let div_history = vec![
+            ("2023-03-31".to_owned(), 1.5),
+            ("2023-06-30".to_owned(), 1.6),
+            ("2023-09-29".to_owned(), 1.6),
+            ("2023-12-29".to_owned(), 1.9),
+            ("2024-03-28".to_owned(), 1.6),
+        ];
+        let as_of = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        // (1.5 + 1.6 + 1.6 + 1.9) / 425 * 100 = 1.55
+        assert_eq!(
+            trailing_div_yield(&div_history, 425.0, as_of).map(|y| (y * 100.0).round() / 100.0),
+            Some(1.55)
+        );
+        assert_eq!(trailing_div_yield(&div_history, 0.0, as_of), None);
+        let as_of = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+        assert_eq!(trailing_div_yield(&div_history, 425.0, as_of), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_weighted_div_yield() -> Result<(), String> {
+        let weights = vec![Some(7.0), Some(3.0), None, Some(5.0)];
+        let yields = vec![Some(0.5), Some(2.0), Some(3.0), None];
+        assert_eq!(weighted_div_yield(&weights, &yields), Some(0.95));
+        assert_eq!(weighted_div_yield(&[None], &[Some(1.0)]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_benchmark_from_constituents() -> Result<(), String> {
+        let path = crate::TempPath::new("constituents.csv");
+        let path = path.as_str();
+        std::fs::write(
+            path,
+            "Symbol,Weight,Div Yield,Date\nAAPL,7.0,0.5,2024-03-01\nXOM,3.0,2.0,2024-03-02\n",
+        )
+        .map_err(|e| e.to_string())?;
+        let benchmark = benchmark_from_constituents(path)?;
+        assert_eq!(benchmark.div_yield, 0.95);
+        assert_eq!(
+            benchmark.as_of,
+            NaiveDate::from_ymd_opt(2024, 3, 2).unwrap()
+        );
+        Ok(())
+    }
+}
@@ -1,6 +1,31 @@
 use calamine::{open_workbook, Xlsx};
 use clap::Parser;
+use investments_forecasting::benchmark::{
+    benchmark_from_constituents, benchmark_from_polygon, Benchmark,
+};
+use investments_forecasting::export::{write_stages, OutputFormat, Stage};
+use investments_forecasting::forecast::forecast_dividend_gains;
+use investments_forecasting::growth::{
+    is_dividend_growth_backed, GrowthTrend, GrowthTrends, ShareholderYield,
+};
+use investments_forecasting::inflation::CpiSeries;
+use investments_forecasting::ranking::{parse_weights, rank, Normalization, Ranking};
+use investments_forecasting::report::{date_to_year, Chart, ChartLine, Report, Section};
+use investments_forecasting::safety::{
+    calculate_safety_score, SafetyInputs, SafetyScore, SAFETY_FACTORS,
+};
+use investments_forecasting::screening::{
+    apply_criteria, optional_f64_column, parse_expression, Criterion, CriterionValue, Screen,
+    ScreenParameters,
+};
+use investments_forecasting::sector::Sector;
+use investments_forecasting::watchlist::{detect_changes, ChangeThresholds, Snapshot, Watchlist};
+use investments_forecasting::HealthMetrics;
 use polars::prelude::*;
+use std::collections::BTreeMap;
+
+mod server;
+mod tui;
 
 // TODO: fix all companies list
 // TODO: make downloading all companies data
@@ -32,10 +57,15 @@ struct Args {
     #[arg(long, default_values_t = &[] )]
     company: Vec<String>,
 
-    /// Average USA inflation during investment time[%]
+    /// Average USA inflation during investment time[%]. Used when "cpi" is not given
     #[arg(long, default_value_t = 3.4)]
     inflation: f64,
 
+    /// CSV file with CPI series ("Date" and either "CPI" index levels or "Inflation" year over
+    /// year rates[%]). Its trailing inflation replaces "inflation"
+    #[arg(long)]
+    cpi: Option<String>,
+
     /// Minimum accepted Dividend Yield[%]
     #[arg(long, default_value_t = 4.7)]
     min_div_yield: f64,
@@ -52,94 +82,1176 @@ struct Args {
     #[arg(long, default_value_t = 75.0)]
     max_div_payout_rate: f64,
 
-    /// Standard and Poor 500 list's average DIV Yield[%]
+    /// Standard and Poor 500 list's average DIV Yield[%]. Used when benchmark is not derived
+    /// from data
     #[arg(long, default_value_t = 1.61)]
     sp500_divy: f64,
+
+    /// Ticker of index ETF (e.g. SPY) whose trailing 12 months Div Yield (from Polygon.io)
+    /// is used as S&P 500 benchmark instead of "sp500_divy"
+    #[arg(long)]
+    benchmark: Option<String>,
+
+    /// CSV file with index constituents ("Symbol", "Weight", "Div Yield"[%] and optional
+    /// "Date" columns) whose weighted Div Yield is used as S&P 500 benchmark instead of
+    /// "sp500_divy"
+    #[arg(long, conflicts_with = "benchmark")]
+    benchmark_constituents: Option<String>,
+
+    /// Minimum accepted Dividend safety score (0-100)
+    #[arg(long, default_value_t = 0.0)]
+    min_safety_score: f64,
+
+    /// Maximum accepted total liabilities to equity ratio
+    #[arg(long)]
+    max_debt_to_equity: Option<f64>,
+
+    /// Maximum accepted net debt to EBITDA ratio
+    #[arg(long)]
+    max_net_debt_to_ebitda: Option<f64>,
+
+    /// Minimum accepted interest coverage (operating income to interest expense)
+    #[arg(long)]
+    min_interest_coverage: Option<f64>,
+
+    /// Minimum accepted current ratio (current assets to current liabilities)
+    #[arg(long)]
+    min_current_ratio: Option<f64>,
+
+    /// Sectors to analyze e.g. "Utilities", "Real Estate". All sectors when not given
+    #[arg(long, default_values_t = &[] )]
+    sector: Vec<String>,
+
+    /// Sectors to be excluded from analysis
+    #[arg(long, default_values_t = &[] )]
+    exclude_sector: Vec<String>,
+
+    /// Evaluate every company against every criterion of 10-11-12 system and explain why
+    /// it passed or failed
+    #[arg(long)]
+    explain: bool,
+
+    /// Failed criterion is reported as near miss when actual value is within this margin
+    /// of the threshold[%]
+    #[arg(long, default_value_t = 10.0)]
+    near_miss_margin: f64,
+
+    /// Screen companies with rules of shipped preset. Possible values: "10-11-12", "high-yield",
+    /// "dividend-growth"
+    #[arg(long)]
+    preset: Option<String>,
+
+    /// Screen companies with rules defined in TOML file
+    #[arg(long, conflicts_with = "preset")]
+    screen: Option<String>,
+
+    /// Rank shortlisted companies by weighted factors and present given number of best ones
+    #[arg(long)]
+    top: Option<usize>,
+
+    /// Normalization of ranking factors within the list. Possible values: "percentile", "zscore"
+    #[arg(long, default_value = "percentile")]
+    rank_normalization: String,
+
+    /// Weight of ranking factor given as <factor>=<weight> e.g. yield=2. Possible factors: "yield",
+    /// "dgr", "streak", "payout", "valuation". All factors are weighted equally when not given
+    #[arg(long, default_values_t = &[] )]
+    rank_weight: Vec<String>,
+
+    /// Name of watchlist to refresh and report changes of since last run. Companies given with
+    /// "company" are added to the watchlist
+    #[arg(long)]
+    watchlist: Option<String>,
+
+    /// Directory where watchlists are stored
+    #[arg(long, default_value = "watchlists")]
+    watchlist_dir: String,
+
+    /// Format of results written to "output" file. Possible values: "csv", "jsonl", "markdown",
+    /// "xlsx" (sheet per screening stage)
+    #[arg(long, default_value = "csv")]
+    output_format: String,
+
+    /// File to write results to
+    #[arg(long)]
+    output: Option<String>,
+
+    /// HTML file to write report of shortlisted companies to. Report is self-contained and
+    /// can be viewed offline
+    #[arg(long)]
+    report: Option<String>,
+
+    /// Capital invested in every shortlisted company in report's forecast[$]
+    #[arg(long, default_value_t = 10000.0)]
+    capital: f64,
+
+    /// Investment time in report's forecast[years]
+    #[arg(long, default_value_t = 4)]
+    years: u32,
+
+    /// Annual share price growth rate used in report's forecast[%]
+    #[arg(long, default_value_t = 7.4)]
+    share_price_growth_rate: f64,
+
+    /// Dividend tax rate used in report's forecast[%]
+    #[arg(long, default_value_t = 15.0)]
+    tax_rate: f64,
+
+    /// Browse companies in terminal UI with thresholds adjustable live. Data is loaded once
+    /// (from "data" list or Polygon.io)
+    #[arg(long)]
+    tui: bool,
+
+    /// Serve analysis and forecasts as JSON over HTTP on localhost. Data is loaded once
+    /// (from "data" list or Polygon.io)
+    #[arg(long, conflicts_with = "tui")]
+    serve: bool,
+
+    /// Port of localhost to serve on
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
 }
 
-fn analyze_div_yield(
+impl Args {
+    fn screen_parameters(&self) -> ScreenParameters {
+        ScreenParameters {
+            sp500_divy: self.sp500_divy,
+            inflation: self.inflation,
+            min_div_yield: self.min_div_yield,
+            max_div_yield: self.max_div_yield,
+            max_div_payout_rate: self.max_div_payout_rate,
+            min_div_growth_rate: self.min_div_growth_rate,
+        }
+    }
+
+    /// S&P 500 benchmark derived from data (if requested)
+    fn benchmark(&self) -> Result<Option<Benchmark>, &'static str> {
+        match (&self.benchmark, &self.benchmark_constituents) {
+            (Some(ticker), _) => benchmark_from_polygon(&ticker.to_uppercase()).map(Some),
+            (None, Some(path)) => benchmark_from_constituents(path).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Screen selected by user, 10-11-12 system when none was selected
+    fn selected_screen(&self) -> Result<Screen, &'static str> {
+        match (&self.screen, &self.preset) {
+            (Some(file), _) => Screen::from_file(file),
+            (None, Some(preset)) => Screen::preset(preset),
+            (None, None) => Screen::preset("10-11-12"),
+        }
+    }
+
+    /// Write screening stages to output file (if requested)
+    fn write_output(&self, stages: &[Stage]) -> Result<(), &'static str> {
+        match &self.output {
+            Some(path) => {
+                let format = OutputFormat::from_name(&self.output_format).ok_or_else(|| {
+                    log::error!("Unknown output format: {}", self.output_format);
+                    "Error: Unknown output format"
+                })?;
+                write_stages(stages, format, path)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Ranking requested by user (if any)
+    fn ranking(&self) -> Result<Option<Ranking>, &'static str> {
+        match self.top {
+            Some(top) => Ok(Some(Ranking {
+                normalization: Normalization::from_name(&self.rank_normalization).ok_or_else(
+                    || {
+                        log::error!("Unknown normalization: {}", self.rank_normalization);
+                        "Error: Unknown ranking normalization"
+                    },
+                )?,
+                factors: parse_weights(&self.rank_weight)?,
+                top,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Arguments of divforecasting run from terminal UI
+    fn forecast_args(&self) -> Vec<String> {
+        let mut forecast_args = vec![
+            "--capital".to_owned(),
+            self.capital.to_string(),
+            "--years".to_owned(),
+            self.years.to_string(),
+            "--share-price-growth-rate".to_owned(),
+            self.share_price_growth_rate.to_string(),
+            "--tax-rate".to_owned(),
+            self.tax_rate.to_string(),
+        ];
+        if let Some(data) = &self.data {
+            forecast_args.push("--data".to_owned());
+            forecast_args.push(data.clone());
+        }
+        forecast_args
+    }
+
+    /// Screen selected by user for Polygon.io data. 10-11-12 system is replaced with its
+    /// variant working on columns available from Polygon.io
+    fn selected_polygon_screen(&self) -> Result<Screen, &'static str> {
+        match (&self.screen, self.preset.as_deref()) {
+            (Some(file), _) => Screen::from_file(file),
+            (None, None) | (None, Some("10-11-12")) => Screen::preset("10-11-12-polygon"),
+            (None, Some(preset)) => Screen::preset(preset),
+        }
+    }
+}
+
+/// Sectors to be analyzed
+#[derive(Debug, Clone, Default)]
+struct SectorFilter {
+    include: Vec<Sector>,
+    exclude: Vec<Sector>,
+}
+
+impl SectorFilter {
+    fn from_args(args: &Args) -> Result<Self, &'static str> {
+        let parse = |names: &Vec<String>| {
+            names
+                .iter()
+                .map(|name| {
+                    Sector::from_name(name).ok_or_else(|| {
+                        log::error!("Unknown sector: {name}");
+                        "Error: Unknown sector name"
+                    })
+                })
+                .collect::<Result<Vec<Sector>, &'static str>>()
+        };
+        Ok(SectorFilter {
+            include: parse(&args.sector)?,
+            exclude: parse(&args.exclude_sector)?,
+        })
+    }
+
+    fn accepts(&self, sector: &Sector) -> bool {
+        (self.include.is_empty() || self.include.contains(sector)) && !self.exclude.contains(sector)
+    }
+}
+
+fn median(values: &mut Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// Balance sheet health thresholds. Companies with no data to check given threshold are
+/// reported and accepted
+#[derive(Debug, Clone, Default)]
+struct HealthThresholds {
+    max_debt_to_equity: Option<f64>,
+    max_net_debt_to_ebitda: Option<f64>,
+    min_interest_coverage: Option<f64>,
+    min_current_ratio: Option<f64>,
+}
+
+impl HealthThresholds {
+    fn from_args(args: &Args) -> Self {
+        HealthThresholds {
+            max_debt_to_equity: args.max_debt_to_equity,
+            max_net_debt_to_ebitda: args.max_net_debt_to_ebitda,
+            min_interest_coverage: args.min_interest_coverage,
+            min_current_ratio: args.min_current_ratio,
+        }
+    }
+
+    fn accepts(&self, symbol: &str, health: &HealthMetrics) -> bool {
+        let check = |name: &str, value: Option<f64>, threshold: Option<f64>, is_max: bool| match (
+            value, threshold,
+        ) {
+            (_, None) => true,
+            (None, Some(_)) => {
+                log::warn!("{symbol}: no data to check {name} threshold");
+                true
+            }
+            (Some(value), Some(threshold)) => {
+                if is_max {
+                    value <= threshold
+                } else {
+                    value >= threshold
+                }
+            }
+        };
+        check(
+            "Debt/Equity",
+            health.debt_to_equity,
+            self.max_debt_to_equity,
+            true,
+        ) && check(
+            "Net Debt/EBITDA",
+            health.net_debt_to_ebitda,
+            self.max_net_debt_to_ebitda,
+            true,
+        ) && check(
+            "Interest Coverage",
+            health.interest_coverage,
+            self.min_interest_coverage,
+            false,
+        ) && check(
+            "Current Ratio",
+            health.current_ratio,
+            self.min_current_ratio,
+            false,
+        )
+    }
+}
+
+const HEALTH_COLUMNS: [&str; 4] = [
+    "Debt/Equity",
+    "Net Debt/EBITDA",
+    "Interest Coverage",
+    "Current Ratio",
+];
+
+/// Balance sheet health metrics as DataFrame columns
+fn health_columns(healths: &[HealthMetrics]) -> Vec<Series> {
+    let metrics: [fn(&HealthMetrics) -> Option<f64>; 4] = [
+        |h| h.debt_to_equity,
+        |h| h.net_debt_to_ebitda,
+        |h| h.interest_coverage,
+        |h| h.current_ratio,
+    ];
+    HEALTH_COLUMNS
+        .iter()
+        .zip(metrics)
+        .map(|(name, metric)| {
+            Series::new(
+                name,
+                healths.iter().map(metric).collect::<Vec<Option<f64>>>(),
+            )
+        })
+        .collect()
+}
+
+fn analyze_sectors(
     df: &DataFrame,
-    sp500_divy: f64,
-    inflation: f64,
-    min_divy: f64,
-    max_divy: f64,
+    sector_filter: &SectorFilter,
 ) -> Result<DataFrame, &'static str> {
-    // Dividend Yield should:
-    // 1. Be higher than inflation rate
-    // 2. be higher than 1.5*S&P500 Div Yield rate
-    // 3. No More than 10% (over 10% is suspecious, check their cash flow)
-    let min_ref_sp500 = sp500_divy * 1.5;
-    let mut minimal_accepted_divy = if min_ref_sp500 > inflation {
-        min_ref_sp500
+    // Sectors of the list are mapped into common taxonomy, so they can be
+    // compared with Polygon.io data
+    let sectors: Vec<Sector> = match df.column("Sector").and_then(|c| c.utf8().cloned()) {
+        Ok(names) => names
+            .into_iter()
+            .map(|name| match name.and_then(Sector::from_name) {
+                Some(sector) => sector,
+                None => {
+                    log::warn!("Unrecognized sector: {name:?}");
+                    Sector::Unknown
+                }
+            })
+            .collect(),
+        Err(_) => {
+            log::warn!("\"Sector\" column is not available");
+            vec![Sector::Unknown; df.height()]
+        }
+    };
+
+    let mask: BooleanChunked = sectors.iter().map(|s| sector_filter.accepts(s)).collect();
+    let mut df = df.clone();
+    df.with_column(Series::new(
+        "Sector",
+        sectors
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>(),
+    ))
+    .map_err(|_| "Unable to update Sector column")?;
+    Ok(df.filter(&mask).expect("Error filtering"))
+}
+
+/// Print number of companies, median yield and median dividend growth per sector
+fn print_sector_summary(
+    df: &DataFrame,
+    divy_column: &str,
+    dgr_column: &str,
+) -> Result<(), &'static str> {
+    let sectors = df
+        .column("Sector")
+        .map_err(|_| "Sector column does not exist!")?
+        .utf8()
+        .map_err(|_| "Sector column is not a text!")?;
+    let divys = optional_f64_column(df, divy_column);
+    let dgrs = optional_f64_column(df, dgr_column);
+
+    let mut per_sector: BTreeMap<String, (u32, Vec<f64>, Vec<f64>)> = BTreeMap::new();
+    sectors
+        .into_iter()
+        .zip(divys.iter().zip(dgrs.iter()))
+        .for_each(|(sector, (divy, dgr))| {
+            let entry = per_sector
+                .entry(sector.unwrap_or("Unknown").to_owned())
+                .or_insert((0, vec![], vec![]));
+            entry.0 += 1;
+            entry.1.extend(divy);
+            entry.2.extend(dgr);
+        });
+
+    let mut names: Vec<String> = vec![];
+    let mut counts: Vec<u32> = vec![];
+    let mut median_divys: Vec<Option<f64>> = vec![];
+    let mut median_dgrs: Vec<Option<f64>> = vec![];
+    per_sector
+        .into_iter()
+        .for_each(|(name, (count, mut divys, mut dgrs))| {
+            names.push(name);
+            counts.push(count);
+            median_divys.push(median(&mut divys));
+            median_dgrs.push(median(&mut dgrs));
+        });
+
+    let summary = DataFrame::new(vec![
+        Series::new("Sector", names),
+        Series::new("Companies", counts),
+        Series::new("Median Div Yield[%]", median_divys),
+        Series::new("Median DGR[%]", median_dgrs),
+    ])
+    .map_err(|_| "Unable to create sector summary")?;
+    println!("Sectors summary: {summary}");
+    Ok(())
+}
+
+/// Values of ranking factors: (factor, XLSX list value, Polygon.io value, only positive values
+/// are meaningful). Values are column names or expressions on two quoted columns
+const RANKING_FACTOR_VALUES: [(&str, &str, &str, bool); 5] = [
+    ("yield", "Div Yield", "Div Yield[%]", false),
+    ("dgr", "DGR 5Y", "DGR5G[%]", false),
+    (
+        "streak",
+        "No Years",
+        "Years of consecutive Div growth",
+        false,
+    ),
+    (
+        "payout",
+        "\"Annualized\" / \"CF/Share\"",
+        "Payout ratio[%]",
+        false,
+    ),
+    // Negative P/E (company making losses) is not a bargain
+    ("valuation", "TTM P/E", "P/E", true),
+];
+
+/// Value of ranking factor for companies from XLSX list or from Polygon.io and whether only
+/// positive values are meaningful
+fn ranking_factor_value(
+    factor: &str,
+    is_polygon: bool,
+) -> Result<(CriterionValue, bool), &'static str> {
+    let (_, list_value, polygon_value, positive_only) = RANKING_FACTOR_VALUES
+        .iter()
+        .find(|(name, _, _, _)| *name == factor)
+        .ok_or_else(|| {
+            log::error!("No values of ranking factor: {factor}");
+            "Error: Unknown ranking factor"
+        })?;
+    let value = if is_polygon {
+        polygon_value
     } else {
-        inflation
+        list_value
     };
-    if min_divy > minimal_accepted_divy {
-        minimal_accepted_divy = min_divy;
+    let value = if value.starts_with('"') {
+        parse_expression(value)?
+    } else {
+        CriterionValue::Column(value.to_string())
     };
+    Ok((value, *positive_only))
+}
 
-    let divy_col = df
-        .column("Div Yield")
-        .map_err(|_| "Div Yield column does not exist!")?;
+/// Rank companies by weighted factors and print best ones with contribution of each factor
+fn print_ranking(df: &DataFrame, ranking: &Ranking, is_polygon: bool) -> Result<(), &'static str> {
+    let values: Vec<Vec<Option<f64>>> = ranking
+        .factors
+        .iter()
+        .map(|factor| {
+            let (value, positive_only) = ranking_factor_value(factor.name, is_polygon)?;
+            let values = value.evaluate(df);
+            Ok(if positive_only {
+                values.into_iter().map(|v| v.filter(|v| *v > 0.0)).collect()
+            } else {
+                values
+            })
+        })
+        .collect::<Result<Vec<Vec<Option<f64>>>, &'static str>>()?;
+    let ranks = rank(ranking, &values);
+
+    let mut order: Vec<usize> = (0..ranks.len()).collect();
+    order.sort_by(|a, b| ranks[*b].score.total_cmp(&ranks[*a].score));
+    order.truncate(ranking.top);
+
+    let symbols: Vec<Option<&str>> = df
+        .column("Symbol")
+        .map_err(|_| "Symbol column does not exist!")?
+        .utf8()
+        .map_err(|_| "Symbol column is not a text!")?
+        .into_iter()
+        .collect();
+    let mut columns = vec![
+        Series::new("Rank", (1..=order.len() as u32).collect::<Vec<u32>>()),
+        Series::new(
+            "Symbol",
+            order
+                .iter()
+                .map(|i| symbols[*i])
+                .collect::<Vec<Option<&str>>>(),
+        ),
+        Series::new(
+            "Score",
+            order.iter().map(|i| ranks[*i].score).collect::<Vec<f64>>(),
+        ),
+    ];
+    ranking.factors.iter().enumerate().for_each(|(f, factor)| {
+        columns.push(Series::new(
+            factor.title,
+            order
+                .iter()
+                .map(|i| ranks[*i].contributions[f])
+                .collect::<Vec<Option<f64>>>(),
+        ))
+    });
+    let ranking_df = DataFrame::new(columns).map_err(|_| "Unable to create ranking")?;
+    println!(
+        "Top {} companies ({:?} of factors weighted {}): {ranking_df}",
+        ranking.top,
+        ranking.normalization,
+        ranking
+            .factors
+            .iter()
+            .map(|f| format!("{}={}", f.name, f.weight))
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    Ok(())
+}
 
-    let mask = divy_col
-        .gt(minimal_accepted_divy)
-        .map_err(|_| "Could not apply filtering data based on Div Yield and Inflation Div Yield")?;
-    let mask2 = divy_col
-        .lt_eq(max_divy)
-        .map_err(|_| "Error creating filter of min_growth_rate")?;
-    let mask = mask & mask2;
+/// Evaluate every company against every criterion. Returns a table with outcome of each
+/// criterion per company and a table of near misses (failed within margin[%] of threshold)
+fn explain_criteria(
+    df: &DataFrame,
+    criteria: &[Criterion],
+    margin: f64,
+) -> Result<(DataFrame, DataFrame), &'static str> {
+    let symbols: Vec<Option<&str>> = df
+        .column("Symbol")
+        .map_err(|_| "Symbol column does not exist!")?
+        .utf8()
+        .map_err(|_| "Symbol column is not a text!")?
+        .into_iter()
+        .collect();
+
+    let mut passed_all = vec![true; df.height()];
+    let mut outcome_columns: Vec<Series> = vec![Series::new("Symbol", &symbols)];
+    let mut near_miss_symbols: Vec<Option<&str>> = vec![];
+    let mut near_miss_criteria: Vec<&str> = vec![];
+    let mut near_miss_values: Vec<f64> = vec![];
+    let mut near_miss_thresholds: Vec<String> = vec![];
+
+    criteria.iter().for_each(|criterion| {
+        let outcomes: Vec<String> = criterion
+            .value
+            .evaluate(df)
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| match value {
+                Some(value) if criterion.comparison.holds(value, criterion.threshold) => {
+                    format!("PASS {value:.2}")
+                }
+                Some(value) => {
+                    passed_all[i] = false;
+                    if criterion.is_near_miss(value, margin) {
+                        near_miss_symbols.push(symbols[i]);
+                        near_miss_criteria.push(&criterion.name);
+                        near_miss_values.push(value);
+                        near_miss_thresholds.push(format!(
+                            "{} {:.2}",
+                            criterion.comparison.symbol(),
+                            criterion.threshold
+                        ));
+                        format!("NEAR MISS {value:.2}")
+                    } else {
+                        format!("FAIL {value:.2}")
+                    }
+                }
+                None => {
+                    passed_all[i] = false;
+                    "NO DATA".to_owned()
+                }
+            })
+            .collect();
+        outcome_columns.push(Series::new(&criterion.name, outcomes));
+    });
+    outcome_columns.push(Series::new("Passed", passed_all));
+
+    let outcomes = DataFrame::new(outcome_columns).map_err(|_| "Unable to create explanation")?;
+    let near_misses = DataFrame::new(vec![
+        Series::new("Symbol", near_miss_symbols),
+        Series::new("Criterion", near_miss_criteria),
+        Series::new("Actual", near_miss_values),
+        Series::new("Required", near_miss_thresholds),
+    ])
+    .map_err(|_| "Unable to create near misses")?;
+    Ok((outcomes, near_misses))
+}
 
-    let filtred_df = df.filter(&mask).expect("Error filtering");
+fn print_explanation(
+    df: &DataFrame,
+    criteria: &[Criterion],
+    margin: f64,
+) -> Result<(), &'static str> {
+    let thresholds = DataFrame::new(vec![
+        Series::new(
+            "Criterion",
+            criteria
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<&str>>(),
+        ),
+        Series::new(
+            "Condition",
+            criteria
+                .iter()
+                .map(|c| format!("{} {} {:.2}", c.value, c.comparison.symbol(), c.threshold))
+                .collect::<Vec<String>>(),
+        ),
+        Series::new(
+            "Threshold derived from",
+            criteria
+                .iter()
+                .map(|c| c.derivation.as_str())
+                .collect::<Vec<&str>>(),
+        ),
+    ])
+    .map_err(|_| "Unable to create criteria table")?;
+    println!("Criteria: {thresholds}");
+
+    let (outcomes, near_misses) = explain_criteria(df, criteria, margin)?;
+    println!("Criteria evaluation: {outcomes}");
+    println!("Near misses (within {margin}% of threshold): {near_misses}");
+    Ok(())
+}
+
+/// Columns of XLSX list presented in terminal UI
+const LIST_TUI_COLUMNS: [&str; 11] = [
+    "Symbol",
+    "Company",
+    "Sector",
+    "No Years",
+    "Price",
+    "Div Yield",
+    "Current Div",
+    "CF/Share",
+    "DGR 1Y",
+    "DGR 5Y",
+    "DGR 10Y",
+];
+
+/// Columns of Polygon.io data presented in terminal UI
+const POLYGON_TUI_COLUMNS: [&str; 9] = [
+    "Symbol",
+    "Sector",
+    "Share Price",
+    "Recent Div",
+    "Div Yield[%]",
+    "DGR5G[%]",
+    "Payout ratio[%]",
+    "Years of consecutive Div growth",
+    "Safety Score",
+];
+
+/// Columns of shortlisted companies used as inputs of dividend gains forecast
+struct ForecastColumns {
+    share_price: &'static str,
+    div_yield: &'static str,
+    dgr: &'static str,
+    /// Number of pay-outs a year. Quarterly pay-outs are assumed when not available
+    frequency: Option<&'static str>,
+}
+
+const LIST_FORECAST_COLUMNS: ForecastColumns = ForecastColumns {
+    share_price: "Price",
+    div_yield: "Div Yield",
+    dgr: "DGR 5Y",
+    frequency: None,
+};
+
+const POLYGON_FORECAST_COLUMNS: ForecastColumns = ForecastColumns {
+    share_price: "Share Price",
+    div_yield: "Div Yield[%]",
+    dgr: "DGR5G[%]",
+    frequency: Some("Annual Frequency"),
+};
+
+fn symbols_of(df: &DataFrame) -> Result<Vec<String>, &'static str> {
+    Ok(df
+        .column("Symbol")
+        .map_err(|_| "Symbol column does not exist!")?
+        .utf8()
+        .map_err(|_| "Symbol column is not a text!")?
+        .into_iter()
+        .map(|s| s.unwrap_or_default().to_owned())
+        .collect())
+}
+
+/// Dividends received (after tax) over investment time for every company of the table
+fn forecast_chart(
+    df: &DataFrame,
+    columns: &ForecastColumns,
+    args: &Args,
+) -> Result<Chart, &'static str> {
+    let symbols = symbols_of(df)?;
+    let prices = optional_f64_column(df, columns.share_price);
+    let divys = optional_f64_column(df, columns.div_yield);
+    let dgrs = optional_f64_column(df, columns.dgr);
+    let frequencies = match columns.frequency {
+        Some(name) => optional_f64_column(df, name),
+        None => vec![None; df.height()],
+    };
+    let time_line: Vec<u32> = (1..365 * args.years + 1).collect();
+
+    let lines: Vec<ChartLine> = (0..df.height())
+        .filter_map(|i| match (prices[i], divys[i], dgrs[i]) {
+            (Some(price), Some(divy), Some(dgr)) if price > 0.0 => {
+                let (_, _, gains) = forecast_dividend_gains(
+                    args.capital,
+                    divy / 100.0,
+                    dgr / 100.0,
+                    price,
+                    args.share_price_growth_rate / 100.0,
+                    args.tax_rate / 100.0,
+                    &time_line,
+                    frequencies[i].map_or(4, |f| f as u32).max(1),
+                );
+                // Weekly points are detailed enough to be plotted
+                let points = time_line
+                    .iter()
+                    .zip(gains)
+                    .filter(|(day, _)| *day % 7 == 0 || **day == 365 * args.years)
+                    .map(|(day, gain)| (*day as f64 / 365.0, gain))
+                    .collect();
+                Some(ChartLine {
+                    caption: symbols[i].clone(),
+                    points,
+                })
+            }
+            _ => {
+                log::warn!("{}: no data to forecast dividend gains", symbols[i]);
+                None
+            }
+        })
+        .collect();
+
+    Ok(Chart {
+        title: format!(
+            "Forecast of dividends gained from investing {}$",
+            args.capital
+        ),
+        x_label: "Years".to_owned(),
+        y_label: "Dividends gained[$]".to_owned(),
+        lines,
+    })
+}
+
+/// Write HTML report of shortlisted companies (if requested). Report presents screening stages,
+/// outcome of every criterion for evaluated companies, dividend history of shortlisted
+/// companies (when available) and forecast of their dividend gains
+fn write_report(
+    args: &Args,
+    stages: &[Stage],
+    evaluated: &DataFrame,
+    criteria: &[Criterion],
+    columns: &ForecastColumns,
+    div_histories: Option<&BTreeMap<String, Vec<(String, f64)>>>,
+) -> Result<(), &'static str> {
+    let path = match &args.report {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let (_, shortlisted) = stages.last().ok_or("Error: Nothing to report")?;
+
+    let mut report = Report::new("Dividend companies report");
+    report.push(Section::Paragraph(format!(
+        "Generated on {}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    )));
+    let funnel = DataFrame::new(vec![
+        Series::new(
+            "Stage",
+            stages
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<&str>>(),
+        ),
+        Series::new(
+            "Companies",
+            stages
+                .iter()
+                .map(|(_, df)| df.height() as u32)
+                .collect::<Vec<u32>>(),
+        ),
+    ])
+    .map_err(|_| "Unable to create stages table")?;
+    report.push(Section::Table("Screening stages".to_owned(), funnel));
+
+    report.push(Section::Heading("Shortlisted companies".to_owned()));
+    report.push(Section::Table(
+        format!("Shortlisted companies ({})", shortlisted.height()),
+        shortlisted.clone(),
+    ));
+
+    report.push(Section::Heading("Screening criteria".to_owned()));
+    let conditions: Vec<String> = criteria
+        .iter()
+        .map(|c| {
+            format!(
+                "{}: {} {} {:.2}",
+                c.name,
+                c.value,
+                c.comparison.symbol(),
+                c.threshold
+            )
+        })
+        .collect();
+    report.push(Section::Paragraph(conditions.join("; ")));
+    let (outcomes, near_misses) = explain_criteria(evaluated, criteria, args.near_miss_margin)?;
+    report.push(Section::Table("Criteria evaluation".to_owned(), outcomes));
+    report.push(Section::Table(
+        format!(
+            "Near misses (within {}% of threshold)",
+            args.near_miss_margin
+        ),
+        near_misses,
+    ));
+
+    report.push(Section::Heading("Dividend history".to_owned()));
+    // Companies of XLSX list have no dividend history, it is fetched from Polygon.io if possible
+    let fetched_histories;
+    let div_histories = match div_histories {
+        None if std::env::var("POLYGON_AUTH_KEY").is_ok() => {
+            fetched_histories = symbols_of(shortlisted)?
+                .into_iter()
+                .filter_map(|symbol| {
+                    match investments_forecasting::get_polygon_div_history(&symbol) {
+                        Ok(history) => Some((symbol, history)),
+                        Err(e) => {
+                            log::warn!("{symbol}: unable to get dividend history: {e}");
+                            None
+                        }
+                    }
+                })
+                .collect::<BTreeMap<String, Vec<(String, f64)>>>();
+            Some(&fetched_histories)
+        }
+        div_histories => div_histories,
+    };
+    match div_histories {
+        Some(histories) => symbols_of(shortlisted)?.iter().for_each(|symbol| {
+            let points: Vec<(f64, f64)> = histories
+                .get(symbol)
+                .map(|history| {
+                    history
+                        .iter()
+                        .filter_map(|(date, div)| date_to_year(date).map(|year| (year, *div)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            report.push(Section::Chart(Chart {
+                title: format!("{symbol} dividend history"),
+                x_label: "Year".to_owned(),
+                y_label: "Dividend[$]".to_owned(),
+                lines: vec![ChartLine {
+                    caption: symbol.clone(),
+                    points,
+                }],
+            }));
+        }),
+        None => report.push(Section::Paragraph(
+            "Dividend history of companies from XLSX list is fetched from Polygon.io only when \
+             POLYGON_AUTH_KEY is set"
+                .to_owned(),
+        )),
+    }
+
+    report.push(Section::Heading("Forecast".to_owned()));
+    report.push(Section::Paragraph(format!(
+        "Investment of {}$ in every company for {} years. Share price growth rate: {}%, \
+         Tax rate: {}%",
+        args.capital, args.years, args.share_price_growth_rate, args.tax_rate
+    )));
+    report.push(Section::Chart(forecast_chart(shortlisted, columns, args)?));
+
+    report.save(path)
+}
 
-    filtred_df
-        .sort(["Div Yield"], true, false)
-        .map_err(|_| "Could not sort along 'Div Yield'")
+/// Safety score, grade and contribution of each factor as DataFrame columns
+fn safety_columns(scores: &[SafetyScore]) -> Vec<Series> {
+    let mut columns = vec![
+        Series::new(
+            "Safety Score",
+            scores.iter().map(|s| s.score).collect::<Vec<f64>>(),
+        ),
+        Series::new(
+            "Safety Grade",
+            scores
+                .iter()
+                .map(|s| s.grade.to_string())
+                .collect::<Vec<String>>(),
+        ),
+    ];
+    SAFETY_FACTORS.iter().enumerate().for_each(|(i, name)| {
+        let points: Vec<Option<f64>> = scores.iter().map(|s| s.factors[i].points).collect();
+        columns.push(Series::new(name, points));
+    });
+    columns
 }
 
-fn analyze_dividend_payout_rate(
+/// Companies of at least given Dividend safety score, sorted along given column (Safety Score
+/// when none is given)
+fn analyze_safety_score(
     df: &DataFrame,
-    max_threshold: f64,
+    min_score: f64,
+    sort_by: Option<&str>,
 ) -> Result<DataFrame, &'static str> {
-    // Dividend Payout rate
-    // 1. Is Current Div / Cash flow per share e.g. 0.22 / 1.7  = 0.129412
-    // 2. No more than 75%
-
-    let cols = df
-        .columns(&["Current Div", "CF/Share"])
-        .map_err(|_| "Current Div and/or CF/Share columns do not exist!")?;
-    let mask = (cols[0] / cols[1])
-        .lt(&Series::new("", &[max_threshold]))
-        .unwrap();
-    let filtred_df = df.filter(&mask).expect("Error filtering");
+    // Dividend safety score is built from data available in the list:
+    // payout ratio (Annualized / CF/Share), streak, Debt/Capital and Div Yield
+    let annualized = optional_f64_column(df, "Annualized");
+    let cf_per_share = optional_f64_column(df, "CF/Share");
+    let streaks = optional_f64_column(df, "No Years");
+    let debt_to_capital = optional_f64_column(df, "Debt/Capital");
+    let divys = optional_f64_column(df, "Div Yield");
+
+    let scores: Vec<SafetyScore> = (0..df.height())
+        .map(|i| {
+            let inputs = SafetyInputs {
+                payout_ratio: match (annualized[i], cf_per_share[i]) {
+                    (Some(div), Some(cf)) if cf > 0.0 => Some(div / cf * 100.0),
+                    _ => None,
+                },
+                years_of_growth: streaks[i].map(|x| x as u32),
+                // D/E = D/(C - D)
+                debt_to_equity: debt_to_capital[i]
+                    .filter(|dc| *dc < 1.0)
+                    .map(|dc| dc / (1.0 - dc)),
+                div_yield: divys[i],
+                ..Default::default()
+            };
+            calculate_safety_score(&inputs)
+        })
+        .collect();
+
+    let scored_df = df
+        .hstack(&safety_columns(&scores))
+        .map_err(|_| "Unable to add Safety Score columns")?;
+    let mask = scored_df
+        .column("Safety Score")
+        .map_err(|_| "Safety Score column does not exist!")?
+        .gt_eq(min_score)
+        .map_err(|_| "Error creating filter of min_safety_score")?;
+    let filtred_df = scored_df.filter(&mask).expect("Error filtering");
+
+    let sort_by = sort_by.unwrap_or("Safety Score");
+    filtred_df.sort([sort_by], true, false).map_err(|_| {
+        log::error!("Could not sort along {sort_by}");
+        "Error: Could not sort scored companies"
+    })
+}
+
+const GROWTH_CAGR_COLUMNS: [&str; 4] = [
+    "Revenue CAGR[%]",
+    "EPS CAGR[%]",
+    "OCF CAGR[%]",
+    "FCF/Share CAGR[%]",
+];
+const GROWTH_TREND_COLUMNS: [&str; 8] = [
+    "Revenue Trend[%/Y]",
+    "EPS Trend[%/Y]",
+    "OCF Trend[%/Y]",
+    "FCF/Share Trend[%/Y]",
+    "Revenue YoY Q[%]",
+    "EPS YoY Q[%]",
+    "OCF YoY Q[%]",
+    "FCF/Share YoY Q[%]",
+];
+
+/// Growth of business figures as DataFrame columns, along with information if dividend
+/// growth is backed by business growth
+fn growth_columns(growths: &[GrowthTrends], dgrs: &[Option<f64>]) -> Vec<Series> {
+    let figures: [fn(&GrowthTrends) -> &GrowthTrend; 4] = [
+        |g| &g.revenue,
+        |g| &g.eps,
+        |g| &g.operating_cash_flow,
+        |g| &g.fcf_per_share,
+    ];
+    let stats: [fn(&GrowthTrend) -> Option<f64>; 3] =
+        [|t| t.cagr, |t| t.trend, |t| t.latest_quarter_yoy];
+    let names = GROWTH_CAGR_COLUMNS
+        .iter()
+        .chain(GROWTH_TREND_COLUMNS.iter());
 
-    filtred_df
-        .sort(["Div Yield"], true, false)
-        .map_err(|_| "Could not sort along 'Div Yield'")
+    let mut columns: Vec<Series> = stats
+        .iter()
+        .flat_map(|stat| figures.iter().map(move |figure| (stat, figure)))
+        .zip(names)
+        .map(|((stat, figure), name)| {
+            Series::new(
+                name,
+                growths
+                    .iter()
+                    .map(|g| stat(figure(g)))
+                    .collect::<Vec<Option<f64>>>(),
+            )
+        })
+        .collect();
+    columns.push(Series::new(
+        "Years of reports",
+        growths
+            .iter()
+            .map(|g| {
+                figures
+                    .iter()
+                    .map(|figure| figure(g).num_years as u32)
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect::<Vec<u32>>(),
+    ));
+    columns.push(Series::new(
+        "DGR backed by growth",
+        growths
+            .iter()
+            .zip(dgrs)
+            .map(|(g, dgr)| dgr.and_then(|dgr| is_dividend_growth_backed(dgr, g)))
+            .collect::<Vec<Option<bool>>>(),
+    ));
+    columns
 }
 
-fn analyze_div_growth(df: &DataFrame, min_growth_rate: f64) -> Result<DataFrame, &'static str> {
-    // Dividend growth rate
-    // 1. 10% min (more or less) depending on historical growth
+const SHAREHOLDER_YIELD_COLUMNS: [&str; 4] = [
+    "Share Count CAGR[%]",
+    "Net Buyback Yield[%]",
+    "Shareholder Yield[%]",
+    "Dilutive Div Payer",
+];
+
+/// Share count trend and shareholder yield as DataFrame columns
+fn shareholder_yield_columns(yields: &[ShareholderYield]) -> Vec<Series> {
+    vec![
+        Series::new(
+            SHAREHOLDER_YIELD_COLUMNS[0],
+            yields
+                .iter()
+                .map(|y| y.share_count_cagr)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            SHAREHOLDER_YIELD_COLUMNS[1],
+            yields
+                .iter()
+                .map(|y| y.net_buyback_yield)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            SHAREHOLDER_YIELD_COLUMNS[2],
+            yields
+                .iter()
+                .map(|y| y.shareholder_yield)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            SHAREHOLDER_YIELD_COLUMNS[3],
+            yields
+                .iter()
+                .map(|y| y.is_dilutive_payer)
+                .collect::<Vec<bool>>(),
+        ),
+    ]
+}
 
-    let min_div_growth_5y_to_10y_ratio = 1.0;
+fn print_shareholder_yield(df: &DataFrame) -> Result<(), &'static str> {
+    let mut columns = vec!["Symbol", "Div Yield[%]"];
+    columns.extend(SHAREHOLDER_YIELD_COLUMNS);
+    let yield_df = df
+        .select(&columns)
+        .map_err(|_| "Unable to select shareholder yield columns!")?;
+    println!("Share count trend and shareholder yield: {yield_df}");
+    Ok(())
+}
 
-    let cols = df
-        .columns(&["DGR 1Y", "DGR 3Y", "DGR 5Y", "DGR 10Y"])
-        .map_err(|_| "DGR (dividend growth) columns do not exist!")?;
-    let mask = (cols[2] / cols[3])
-        .gt_eq(&Series::new("", &[min_div_growth_5y_to_10y_ratio]))
-        .unwrap();
-    let mask2 = cols[0]
-        .gt_eq(min_growth_rate)
-        .map_err(|_| "Error creating filter of min_growth_rate")?;
-    let mask = mask & mask2;
+fn print_growth_trends(df: &DataFrame) -> Result<(), &'static str> {
+    let mut columns = vec!["Symbol", "DGR5G[%]"];
+    columns.extend(GROWTH_CAGR_COLUMNS);
+    columns.extend(["Years of reports", "DGR backed by growth"]);
+    let cagr_df = df
+        .select(&columns)
+        .map_err(|_| "Unable to select growth columns!")?;
+    println!("Growth of annual figures: {cagr_df}");
+
+    let mut columns = vec!["Symbol"];
+    columns.extend(GROWTH_TREND_COLUMNS);
+    let trend_df = df
+        .select(&columns)
+        .map_err(|_| "Unable to select growth trend columns!")?;
+    println!("Growth trends: {trend_df}");
+    Ok(())
+}
 
-    let filtred_df = df.filter(&mask).expect("Error filtering");
+fn analyze_balance_sheet_health(
+    df: &DataFrame,
+    thresholds: &HealthThresholds,
+) -> Result<DataFrame, &'static str> {
+    // Dividend list provides Debt/Capital and Current Ratio only
+    let symbols = df
+        .column("Symbol")
+        .map_err(|_| "Symbol column does not exist!")?
+        .utf8()
+        .map_err(|_| "Symbol column is not a text!")?
+        .into_iter()
+        .map(|x| x.unwrap_or_default().to_owned())
+        .collect::<Vec<String>>();
+    let debt_to_capital = optional_f64_column(df, "Debt/Capital");
+    let current_ratios = optional_f64_column(df, "Current R");
+
+    let healths: Vec<HealthMetrics> = (0..df.height())
+        .map(|i| HealthMetrics {
+            // D/E = D/(C - D)
+            debt_to_equity: debt_to_capital[i]
+                .filter(|dc| *dc < 1.0)
+                .map(|dc| dc / (1.0 - dc)),
+            current_ratio: current_ratios[i],
+            ..Default::default()
+        })
+        .collect();
+
+    let mask: BooleanChunked = healths
+        .iter()
+        .zip(symbols.iter())
+        .map(|(health, symbol)| thresholds.accepts(symbol, health))
+        .collect();
+
+    let df = df
+        .hstack(&health_columns(&healths))
+        .map_err(|_| "Unable to add balance sheet health columns")?;
+    Ok(df.filter(&mask).expect("Error filtering"))
+}
 
-    filtred_df
-        .sort(["DGR 1Y"], true, false)
-        .map_err(|_| "Could not sort along 'DGR 1Y'")
+fn print_safety_breakdown(df: &DataFrame) -> Result<(), &'static str> {
+    let mut columns = vec!["Symbol"];
+    columns.extend(SAFETY_FACTORS);
+    let breakdown_df = df
+        .select(&columns)
+        .map_err(|_| "Unable to select Safety factors columns!")?;
+    println!("Dividend safety score breakdown (points per factor): {breakdown_df}");
+    Ok(())
 }
 
 fn print_summary(df: &DataFrame, company: Option<&str>) -> Result<(), &'static str> {
@@ -171,10 +1283,185 @@ fn print_summary(df: &DataFrame, company: Option<&str>) -> Result<(), &'static s
     selected_df
         .with_column(rate.clone())
         .expect("Unable to add Rate column");
+    let is_scored = dfs.get_column_names().contains(&"Safety Score");
+    if is_scored {
+        selected_df = selected_df
+            .hstack(
+                &dfs.columns(&["Safety Score", "Safety Grade"])
+                    .map_err(|_| "Unable to select Safety Score columns!")?
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<Series>>(),
+            )
+            .map_err(|_| "Unable to add Safety Score columns")?;
+    }
     println!("{selected_df}");
+    if dfs.get_column_names().contains(&HEALTH_COLUMNS[0]) {
+        let mut columns = vec!["Symbol"];
+        columns.extend(HEALTH_COLUMNS);
+        let health_df = dfs
+            .select(&columns)
+            .map_err(|_| "Unable to select balance sheet health columns!")?;
+        println!("Balance sheet health: {health_df}");
+    }
+    if is_scored {
+        print_safety_breakdown(&dfs)?;
+    }
     Ok(())
 }
 
+fn snapshot_from_polygon(symbol: &str, data: &investments_forecasting::PolygonData) -> Snapshot {
+    Snapshot {
+        symbol: symbol.to_owned(),
+        price: Some(data.share_price),
+        curr_div: data.curr_div,
+        div_yield: data.divy,
+        dgr: data.dgr,
+        payout_ratio: data.payout_ratio,
+        streak: data.years_of_growth,
+    }
+}
+
+fn snapshot_from_list(df: &DataFrame, symbol: &str) -> Result<Snapshot, &'static str> {
+    let mask = df
+        .column("Symbol")
+        .map_err(|_| "Error: Unable to get Symbol")?
+        .equal(symbol)
+        .map_err(|_| "Error: Unable to create mask")?;
+    let dfs = df
+        .filter(&mask)
+        .map_err(|_| "Error: Unable to get Symbol")?;
+    if dfs.height() == 0 {
+        return Err("Company symbol not present in selected List");
+    }
+    let value = |name: &str| optional_f64_column(&dfs, name)[0];
+    Ok(Snapshot {
+        symbol: symbol.to_owned(),
+        price: value("Price"),
+        curr_div: value("Current Div"),
+        div_yield: value("Div Yield"),
+        dgr: value("DGR 5Y"),
+        // Same basis as Div Payout of 10-11-12 screen, so it compares with --max-div-payout-rate
+        payout_ratio: match (value("Current Div"), value("CF/Share")) {
+            (Some(div), Some(cf)) if cf != 0.0 => Some(div / cf * 100.0),
+            _ => None,
+        },
+        streak: value("No Years").map(|years| years as u32),
+    })
+}
+
+/// Refresh data of watched companies (from XLSX list if given or Polygon.io), print what
+/// changed since last run and store new snapshots
+fn refresh_watchlist(
+    name: &str,
+    dir: &str,
+    data: Option<&DataFrame>,
+    companies: &[String],
+    thresholds: &ChangeThresholds,
+) -> Result<(), &'static str> {
+    let mut watchlist = Watchlist::load(dir, name)?;
+    watchlist.add(companies);
+    if watchlist.tickers.is_empty() {
+        return Err("Error: Watchlist is empty. Add companies with --company");
+    }
+
+    let mut snapshots: Vec<Snapshot> = vec![];
+    let mut changed_symbols: Vec<String> = vec![];
+    let mut changes: Vec<String> = vec![];
+    watchlist.tickers.iter().for_each(|symbol| {
+        let snapshot = match data {
+            Some(df) => snapshot_from_list(df, symbol),
+            None => investments_forecasting::get_polygon_data(symbol)
+                .map(|polygon_data| snapshot_from_polygon(symbol, &polygon_data)),
+        };
+        match snapshot {
+            Ok(snapshot) => {
+                detect_changes(watchlist.snapshot(symbol), &snapshot, thresholds)
+                    .into_iter()
+                    .for_each(|change| {
+                        changed_symbols.push(symbol.clone());
+                        changes.push(change.to_string());
+                    });
+                snapshots.push(snapshot);
+            }
+            Err(e) => {
+                log::warn!("{symbol}: Unable to refresh ({e}). Keeping previous snapshot");
+                if let Some(previous) = watchlist.snapshot(symbol) {
+                    snapshots.push(previous.clone());
+                }
+            }
+        }
+    });
+
+    let snapshots_df = DataFrame::new(vec![
+        Series::new(
+            "Symbol",
+            snapshots
+                .iter()
+                .map(|s| s.symbol.as_str())
+                .collect::<Vec<&str>>(),
+        ),
+        Series::new(
+            "Price",
+            snapshots
+                .iter()
+                .map(|s| s.price)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            "Recent Div",
+            snapshots
+                .iter()
+                .map(|s| s.curr_div)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            "Div Yield[%]",
+            snapshots
+                .iter()
+                .map(|s| s.div_yield)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            "DGR[%]",
+            snapshots
+                .iter()
+                .map(|s| s.dgr)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            "Payout ratio[%]",
+            snapshots
+                .iter()
+                .map(|s| s.payout_ratio)
+                .collect::<Vec<Option<f64>>>(),
+        ),
+        Series::new(
+            "Years of consecutive Div growth",
+            snapshots
+                .iter()
+                .map(|s| s.streak)
+                .collect::<Vec<Option<u32>>>(),
+        ),
+    ])
+    .map_err(|_| "Unable to create watchlist table")?;
+    println!("Watchlist \"{name}\": {snapshots_df}");
+
+    let changes_df = DataFrame::new(vec![
+        Series::new("Symbol", changed_symbols),
+        Series::new("Change", changes),
+    ])
+    .map_err(|_| "Unable to create watchlist changes table")?;
+    match &watchlist.updated {
+        Some(updated) => println!("Changes since {updated}: {changes_df}"),
+        None => println!("Changes: {changes_df}"),
+    }
+
+    watchlist.snapshots = snapshots;
+    watchlist.updated = Some(chrono::Local::now().format("%Y-%m-%d").to_string());
+    watchlist.save(dir)
+}
+
 fn configure_dataframes_format() {
     // Make sure to show all columns
     if std::env::var("POLARS_FMT_MAX_COLS").is_err() {
@@ -190,37 +1477,21 @@ fn configure_dataframes_format() {
     }
 }
 
-fn get_polygon_companies_data(companies: &Vec<String>) -> Result<(), &'static str> {
+fn get_polygon_companies_data(
+    companies: &Vec<String>,
+    min_safety_score: f64,
+    health_thresholds: &HealthThresholds,
+    sector_filter: &SectorFilter,
+    criteria: Option<&[Criterion]>,
+    explanation: Option<(&[Criterion], f64)>,
+    ranking: Option<&Ranking>,
+) -> Result<(Vec<Stage>, BTreeMap<String, Vec<(String, f64)>>), &'static str> {
     let mut symbols: Vec<&str> = vec![];
-    let mut share_prices: Vec<f64> = vec![];
-    let mut curr_divs: Vec<Option<f64>> = vec![];
-    let mut divys: Vec<Option<f64>> = vec![];
-    let mut freqs: Vec<Option<u32>> = vec![];
-    let mut dgrs: Vec<Option<f64>> = vec![];
-    let mut years_growth: Vec<Option<u32>> = vec![];
-    let mut payout_ratios: Vec<Option<f64>> = vec![];
-    let mut sectors: Vec<Option<String>> = vec![];
+    let mut companies_data: Vec<investments_forecasting::PolygonData> = vec![];
     let maybe_success = companies.iter().try_for_each(|symbol| {
-        let (
-            share_price,
-            curr_div,
-            divy,
-            frequency,
-            dgr,
-            years_of_growth,
-            payout_ratio,
-            sector_desc,
-        ) = investments_forecasting::get_polygon_data(&symbol)?;
-
-        share_prices.push(share_price);
-        curr_divs.push(curr_div);
-        divys.push(divy);
-        freqs.push(frequency);
-        dgrs.push(dgr);
-        years_growth.push(years_of_growth);
-        payout_ratios.push(payout_ratio);
+        let polygon_data = investments_forecasting::get_polygon_data(&symbol)?;
+        companies_data.push(polygon_data);
         symbols.push(&symbol);
-        sectors.push(sector_desc);
         Ok::<(), &'static str>(())
     });
 
@@ -230,22 +1501,242 @@ fn get_polygon_companies_data(companies: &Vec<String>) -> Result<(), &'static st
     }
 
     let s1 = Series::new("Symbol", &symbols);
-    let s2 = Series::new("Share Price", share_prices);
-    let s3 = Series::new("Recent Div", curr_divs);
-    let s4 = Series::new("Annual Frequency", freqs);
-    let s5 = Series::new("Div Yield[%]", divys);
-    let s6 = Series::new("DGR5G[%]", dgrs);
-    let s7 = Series::new("Years of consecutive Div growth", years_growth);
-    let s8 = Series::new("Payout ratio[%]", payout_ratios);
-    let s9 = Series::new("Industry Desc", sectors);
-
-    let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6, s7, s8, s9]).unwrap();
+    let s2 = Series::new(
+        "Share Price",
+        companies_data
+            .iter()
+            .map(|x| x.share_price)
+            .collect::<Vec<f64>>(),
+    );
+    let s3 = Series::new(
+        "Recent Div",
+        companies_data
+            .iter()
+            .map(|x| x.curr_div)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s4 = Series::new(
+        "Annual Frequency",
+        companies_data
+            .iter()
+            .map(|x| x.frequency)
+            .collect::<Vec<Option<u32>>>(),
+    );
+    let s5 = Series::new(
+        "Div Yield[%]",
+        companies_data
+            .iter()
+            .map(|x| x.divy)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s6 = Series::new(
+        "DGR5G[%]",
+        companies_data
+            .iter()
+            .map(|x| x.dgr)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s7 = Series::new(
+        "Years of consecutive Div growth",
+        companies_data
+            .iter()
+            .map(|x| x.years_of_growth)
+            .collect::<Vec<Option<u32>>>(),
+    );
+    let s8 = Series::new(
+        "Payout ratio[%]",
+        companies_data
+            .iter()
+            .map(|x| x.payout_ratio)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s9 = Series::new(
+        "Industry Desc",
+        companies_data
+            .iter()
+            .map(|x| x.sector_desc.clone())
+            .collect::<Vec<Option<String>>>(),
+    );
+
+    let (sectors, industries): (Vec<Sector>, Vec<Option<String>>) =
+        companies_data.iter().map(|x| x.sector()).unzip();
+    let sector_mask: BooleanChunked = sectors.iter().map(|s| sector_filter.accepts(s)).collect();
+    let s16 = Series::new(
+        "Sector",
+        sectors
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>(),
+    );
+    let s17 = Series::new("Industry", industries);
+
+    let valuations: Vec<investments_forecasting::ValuationMetrics> =
+        companies_data.iter().map(|x| x.valuation()).collect();
+    let s10 = Series::new(
+        "EPS",
+        valuations
+            .iter()
+            .map(|x| x.eps)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s11 = Series::new(
+        "P/E",
+        valuations
+            .iter()
+            .map(|x| x.pe)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s12 = Series::new(
+        "P/FCF",
+        valuations
+            .iter()
+            .map(|x| x.pfcf)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s13 = Series::new(
+        "Book Value/Share",
+        valuations
+            .iter()
+            .map(|x| x.bvps)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s14 = Series::new(
+        "P/B",
+        valuations
+            .iter()
+            .map(|x| x.pb)
+            .collect::<Vec<Option<f64>>>(),
+    );
+    let s15 = Series::new(
+        "Graham Number",
+        valuations
+            .iter()
+            .map(|x| x.graham_number)
+            .collect::<Vec<Option<f64>>>(),
+    );
+
+    let healths: Vec<HealthMetrics> = companies_data.iter().map(|x| x.health()).collect();
+    let health_mask: BooleanChunked = healths
+        .iter()
+        .zip(symbols.iter())
+        .map(|(health, symbol)| health_thresholds.accepts(symbol, health))
+        .collect();
+
+    let scores: Vec<SafetyScore> = companies_data
+        .iter()
+        .map(|x| calculate_safety_score(&x.safety_inputs()))
+        .collect();
+
+    let df: DataFrame = DataFrame::new(vec![
+        s1, s2, s3, s4, s5, s6, s7, s8, s9, s16, s17, s10, s11, s12, s13, s14, s15,
+    ])
+    .unwrap();
+    let df = df
+        .hstack(&health_columns(&healths))
+        .map_err(|_| "Unable to add balance sheet health columns")?;
+    let growths: Vec<GrowthTrends> = companies_data.iter().map(|x| x.growth()).collect();
+    let dgrs: Vec<Option<f64>> = companies_data.iter().map(|x| x.dgr).collect();
     let df = df
-        .sort(["Years of consecutive Div growth"], true, false)
+        .hstack(&growth_columns(&growths, &dgrs))
+        .map_err(|_| "Unable to add growth columns")?;
+    let yields: Vec<ShareholderYield> = companies_data
+        .iter()
+        .map(|x| x.shareholder_yield())
+        .collect();
+    yields
+        .iter()
+        .zip(symbols.iter())
+        .filter(|(y, _)| y.is_dilutive_payer)
+        .for_each(|(_, symbol)| log::warn!("{symbol}: dividends are funded by issuing new shares"));
+    let df = df
+        .hstack(&shareholder_yield_columns(&yields))
+        .map_err(|_| "Unable to add shareholder yield columns")?;
+    let df = df
+        .hstack(&safety_columns(&scores))
+        .map_err(|_| "Unable to add Safety Score columns")?;
+    if let Some((criteria, margin)) = explanation {
+        print_explanation(&df, criteria, margin)?;
+    }
+    let mut stages: Vec<Stage> = vec![("All".to_owned(), df.clone())];
+    let mask = df
+        .column("Safety Score")
+        .map_err(|_| "Safety Score column does not exist!")?
+        .gt_eq(min_safety_score)
+        .map_err(|_| "Error creating filter of min_safety_score")?;
+    let df = df
+        .filter(&(mask & health_mask & sector_mask))
+        .expect("Error filtering");
+    stages.push(("Safety, Health and Sector".to_owned(), df.clone()));
+    let df = match criteria {
+        Some(criteria) => {
+            let df = apply_criteria(&df, criteria, None)?;
+            stages.push(("Screen".to_owned(), df.clone()));
+            df
+        }
+        None => df,
+    };
+    let df = df
+        .sort(
+            ["Safety Score", "Years of consecutive Div growth"],
+            vec![true, true],
+            false,
+        )
         .unwrap();
-    println!("{df}");
+    let mut columns = df.get_column_names();
+    columns.retain(|c| {
+        !SAFETY_FACTORS.contains(c)
+            && !GROWTH_CAGR_COLUMNS.contains(c)
+            && !GROWTH_TREND_COLUMNS.contains(c)
+            && !["Years of reports", "DGR backed by growth"].contains(c)
+            && !SHAREHOLDER_YIELD_COLUMNS.contains(c)
+    });
+    println!(
+        "{}",
+        df.select(&columns)
+            .map_err(|_| "Unable to select companies columns!")?
+    );
+    print_growth_trends(&df)?;
+    print_shareholder_yield(&df)?;
+    print_safety_breakdown(&df)?;
+    print_sector_summary(&df, "Div Yield[%]", "DGR5G[%]")?;
+    if let Some(ranking) = ranking {
+        print_ranking(&df, ranking, true)?;
+    }
 
-    Ok(())
+    let div_histories = symbols
+        .iter()
+        .zip(companies_data.iter())
+        .map(|(symbol, data)| (symbol.to_string(), data.div_history.clone()))
+        .collect();
+    Ok((stages, div_histories))
+}
+
+/// Fetch data of given companies (all available when none is given) from Polygon.io, so it
+/// can be browsed or served. Returns companies that passed safety, health and sector filters
+/// with their dividend history
+fn load_polygon_companies(
+    args: &Args,
+    companies: &[String],
+) -> Result<(DataFrame, BTreeMap<String, Vec<(String, f64)>>), &'static str> {
+    let symbols: Vec<String> = if companies.is_empty() {
+        investments_forecasting::get_polygon_companies_list()?
+            .into_iter()
+            .map(|(symbol, _)| symbol)
+            .collect()
+    } else {
+        companies.to_vec()
+    };
+    let (mut stages, div_histories) = get_polygon_companies_data(
+        &symbols,
+        args.min_safety_score,
+        &HealthThresholds::from_args(args),
+        &SectorFilter::from_args(args)?,
+        None,
+        None,
+        None,
+    )?;
+    let (_, companies) = stages.pop().ok_or("Error: No companies were loaded")?;
+    Ok((companies, div_histories))
 }
 
 fn main() -> Result<(), &'static str> {
@@ -253,9 +1744,26 @@ fn main() -> Result<(), &'static str> {
 
     configure_dataframes_format();
 
-    let args = Args::parse();
+    let mut args = Args::parse();
+    match args.benchmark()? {
+        Some(benchmark) => {
+            println!("S&P 500 benchmark Div Yield: {benchmark}");
+            args.sp500_divy = benchmark.div_yield;
+        }
+        None => println!(
+            "S&P 500 benchmark Div Yield: {:.2}% (--sp500-divy)",
+            args.sp500_divy
+        ),
+    }
+    if let Some(path) = &args.cpi {
+        let (as_of, inflation) = CpiSeries::from_csv(path)?
+            .trailing_inflation()
+            .ok_or("Error: Not enough CPI data")?;
+        println!("Inflation: {inflation:.2}% (trailing 12 months from {path}, as of {as_of})");
+        args.inflation = inflation;
+    }
 
-    let data = if let Some(data_file) = args.data {
+    let data = if let Some(data_file) = &args.data {
         let mut excel: Xlsx<_> = open_workbook(data_file).map_err(|_| "Error: opening XLSX")?;
         // Champions
         let data = investments_forecasting::load_list(&mut excel, &args.list)?;
@@ -270,6 +1778,72 @@ fn main() -> Result<(), &'static str> {
         .iter()
         .map(|x| x.to_uppercase())
         .collect::<Vec<String>>();
+    if let Some(watchlist) = &args.watchlist {
+        return refresh_watchlist(
+            watchlist,
+            &args.watchlist_dir,
+            data.as_ref(),
+            &companies,
+            &ChangeThresholds {
+                min_div_yield: args.min_div_yield,
+                max_div_payout_rate: args.max_div_payout_rate,
+            },
+        );
+    }
+
+    if args.tui {
+        let browser = match data {
+            Some(data) => tui::Browser::new(
+                analyze_sectors(&data, &SectorFilter::from_args(&args)?)?,
+                &LIST_TUI_COLUMNS,
+                None,
+                args.selected_screen()?,
+                args.screen_parameters(),
+                args.forecast_args(),
+            )?,
+            None => {
+                let (companies, div_histories) = load_polygon_companies(&args, &companies)?;
+                tui::Browser::new(
+                    companies,
+                    &POLYGON_TUI_COLUMNS,
+                    Some(div_histories),
+                    args.selected_polygon_screen()?,
+                    args.screen_parameters(),
+                    args.forecast_args(),
+                )?
+            }
+        };
+        return tui::run(browser);
+    }
+
+    if args.serve {
+        let forecast = server::ForecastDefaults {
+            capital: args.capital,
+            years: args.years,
+            share_price_growth_rate: args.share_price_growth_rate,
+            tax_rate: args.tax_rate,
+        };
+        let api = match data {
+            Some(data) => server::Api::new(
+                analyze_sectors(&data, &SectorFilter::from_args(&args)?)?,
+                args.selected_screen()?,
+                args.screen_parameters(),
+                false,
+                &LIST_FORECAST_COLUMNS,
+                forecast,
+            ),
+            None => server::Api::new(
+                load_polygon_companies(&args, &companies)?.0,
+                args.selected_polygon_screen()?,
+                args.screen_parameters(),
+                true,
+                &POLYGON_FORECAST_COLUMNS,
+                forecast,
+            ),
+        };
+        return server::serve(&api, args.port);
+    }
+
     // For no handpicked companies just make overall analysis
     if companies.len() == 0 {
         if args.list_all {
@@ -295,29 +1869,67 @@ fn main() -> Result<(), &'static str> {
         } else {
             match data {
                 Some(data) => {
-                    let data_shortlisted_dy = analyze_div_yield(
-                        &data,
-                        args.sp500_divy,
-                        args.inflation,
-                        args.min_div_yield,
-                        args.max_div_yield,
+                    let data_shortlisted_sc =
+                        analyze_sectors(&data, &SectorFilter::from_args(&args)?)?;
+                    let mut stages: Vec<Stage> =
+                        vec![("Sector".to_owned(), data_shortlisted_sc.clone())];
+
+                    if args.explain {
+                        print_explanation(
+                            &data_shortlisted_sc,
+                            &args
+                                .selected_screen()?
+                                .criteria(&args.screen_parameters())?,
+                            args.near_miss_margin,
+                        )?;
+                    }
+
+                    let screen = args.selected_screen()?;
+                    log::info!("Screening with \"{}\": {}", screen.name, screen.description);
+                    let data_shortlisted_dy_dp_dg = apply_criteria(
+                        &data_shortlisted_sc,
+                        &screen.criteria(&args.screen_parameters())?,
+                        screen.sort_by.as_deref(),
                     )?;
-                    log::info!("Champions Shortlisted by DivY: {}", data_shortlisted_dy);
+                    stages.push((screen.name, data_shortlisted_dy_dp_dg.clone()));
 
-                    let data_shortlisted_dy_dp = analyze_dividend_payout_rate(
-                        &data_shortlisted_dy,
-                        args.max_div_payout_rate / 100.0,
+                    let data_shortlisted_dy_dp_dg_bh = analyze_balance_sheet_health(
+                        &data_shortlisted_dy_dp_dg,
+                        &HealthThresholds::from_args(&args),
                     )?;
 
-                    log::info!(
-                        "Champions Shortlisted by DivY and Div Pay-Out: {}",
-                        data_shortlisted_dy_dp
-                    );
+                    stages.push((
+                        "Balance Sheet".to_owned(),
+                        data_shortlisted_dy_dp_dg_bh.clone(),
+                    ));
 
-                    let data_shortlisted_dy_dp_dg =
-                        analyze_div_growth(&data_shortlisted_dy_dp, args.min_div_growth_rate)?;
-
-                    print_summary(&data_shortlisted_dy_dp_dg, None)?;
+                    // Scored companies keep ordering of the screen
+                    let data_shortlisted_dy_dp_dg_ss = analyze_safety_score(
+                        &data_shortlisted_dy_dp_dg_bh,
+                        args.min_safety_score,
+                        screen.sort_by.as_deref(),
+                    )?;
+                    stages.push((
+                        "Safety Score".to_owned(),
+                        data_shortlisted_dy_dp_dg_ss.clone(),
+                    ));
+
+                    print_summary(&data_shortlisted_dy_dp_dg_ss, None)?;
+                    print_sector_summary(&data_shortlisted_dy_dp_dg_ss, "Div Yield", "DGR 5Y")?;
+                    if let Some(ranking) = args.ranking()? {
+                        print_ranking(&data_shortlisted_dy_dp_dg_ss, &ranking, false)?;
+                    }
+                    args.write_output(&stages)?;
+                    write_report(
+                        &args,
+                        &stages,
+                        &data_shortlisted_sc,
+                        &args
+                            .selected_screen()?
+                            .criteria(&args.screen_parameters())?,
+                        &LIST_FORECAST_COLUMNS,
+                        None,
+                    )?;
                 }
                 None => {
                     let companies = investments_forecasting::get_polygon_companies_list()?;
@@ -327,7 +1939,28 @@ fn main() -> Result<(), &'static str> {
                     companies.into_iter().for_each(|(s, _)| {
                         symbols.push(s);
                     });
-                    get_polygon_companies_data(&symbols)?;
+                    let criteria = args
+                        .selected_polygon_screen()?
+                        .criteria(&args.screen_parameters())?;
+                    let (stages, div_histories) = get_polygon_companies_data(
+                        &symbols,
+                        args.min_safety_score,
+                        &HealthThresholds::from_args(&args),
+                        &SectorFilter::from_args(&args)?,
+                        Some(criteria.as_slice()),
+                        args.explain
+                            .then_some((criteria.as_slice(), args.near_miss_margin)),
+                        args.ranking()?.as_ref(),
+                    )?;
+                    args.write_output(&stages)?;
+                    write_report(
+                        &args,
+                        &stages,
+                        &stages[0].1,
+                        &criteria,
+                        &POLYGON_FORECAST_COLUMNS,
+                        Some(&div_histories),
+                    )?;
                 }
             }
         }
@@ -337,12 +1970,65 @@ fn main() -> Result<(), &'static str> {
                 companies
                     .iter()
                     .try_for_each(|symbol| print_summary(&data, Some(&symbol)))?;
+                let mask: BooleanChunked = data
+                    .column("Symbol")
+                    .map_err(|_| "Error: Unable to get Symbol")?
+                    .utf8()
+                    .map_err(|_| "Error: Symbol is not a text")?
+                    .into_iter()
+                    .map(|s| s.map_or(false, |s| companies.iter().any(|c| c == s)))
+                    .collect();
+                let selected = data.filter(&mask).expect("Error filtering");
+                if args.explain {
+                    print_explanation(
+                        &selected,
+                        &args
+                            .selected_screen()?
+                            .criteria(&args.screen_parameters())?,
+                        args.near_miss_margin,
+                    )?;
+                }
+                let stages = vec![("Companies".to_owned(), selected)];
+                args.write_output(&stages)?;
+                write_report(
+                    &args,
+                    &stages,
+                    &stages[0].1,
+                    &args
+                        .selected_screen()?
+                        .criteria(&args.screen_parameters())?,
+                    &LIST_FORECAST_COLUMNS,
+                    None,
+                )?;
             }
             None => {
                 // let (symbols, share_prices, curr_divs, divys, freqs, dgrs, years_growth,
                 //      payout_ratios, sectors) = get_polygon_companies_data(&companies)?;
 
-                get_polygon_companies_data(&companies)?;
+                // Handpicked companies are screened only on request
+                let criteria = args
+                    .selected_polygon_screen()?
+                    .criteria(&args.screen_parameters())?;
+                let is_screened = args.screen.is_some() || args.preset.is_some();
+                let (stages, div_histories) = get_polygon_companies_data(
+                    &companies,
+                    args.min_safety_score,
+                    &HealthThresholds::from_args(&args),
+                    &SectorFilter::from_args(&args)?,
+                    is_screened.then_some(criteria.as_slice()),
+                    args.explain
+                        .then_some((criteria.as_slice(), args.near_miss_margin)),
+                    args.ranking()?.as_ref(),
+                )?;
+                args.write_output(&stages)?;
+                write_report(
+                    &args,
+                    &stages,
+                    &stages[0].1,
+                    &criteria,
+                    &POLYGON_FORECAST_COLUMNS,
+                    Some(&div_histories),
+                )?;
             }
         }
     }
@@ -352,6 +2038,10 @@ fn main() -> Result<(), &'static str> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use investments_forecasting::ranking::RANKING_FACTORS;
+    use investments_forecasting::screening::{
+        analyze_div_growth, analyze_div_yield, analyze_dividend_payout_rate,
+    };
 
     #[test]
     fn test_analyze_divy() -> Result<(), String> {
@@ -475,4 +2165,429 @@ mod tests {
         assert!(result.frame_equal(&ref_df));
         Ok(())
     }
+
+    fn screen_parameters(
+        min_div_yield: f64,
+        max_div_yield: f64,
+        max_div_payout_rate: f64,
+        min_div_growth_rate: f64,
+    ) -> ScreenParameters {
+        ScreenParameters {
+            sp500_divy: 1.61,
+            inflation: 3.4,
+            min_div_yield,
+            max_div_yield,
+            max_div_payout_rate,
+            min_div_growth_rate,
+        }
+    }
+
+    /// Companies passing given rules of 10-11-12 preset, sorted along given column
+    fn screen_10_11_12(
+        df: &DataFrame,
+        params: &ScreenParameters,
+        rules: &[&str],
+        sort_by: &str,
+    ) -> Result<DataFrame, String> {
+        let criteria: Vec<Criterion> = Screen::preset("10-11-12")?
+            .criteria(params)?
+            .into_iter()
+            .filter(|c| rules.contains(&c.name.as_str()))
+            .collect();
+        assert_eq!(criteria.len(), rules.len());
+        Ok(apply_criteria(df, &criteria, Some(sort_by))?)
+    }
+
+    #[test]
+    fn test_screen_divy() -> Result<(), String> {
+        let max_divy = 10.0;
+        let min_divy = 3.9;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32, 4.0]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let s1 = Series::new("Symbol", &["ABM", "CAT"]);
+        let s2 = Series::new("Div Yield", &[5.54, 4.0]);
+
+        let ref_df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let result = screen_10_11_12(
+            &df,
+            &screen_parameters(min_divy, max_divy, 75.0, 10.0),
+            &["Min Div Yield", "Max Div Yield"],
+            "Div Yield",
+        )?;
+        assert!(result.frame_equal(&ref_df));
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_divy_min() -> Result<(), String> {
+        let max_divy = 10.0;
+        let min_divy = 5.0;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT"]);
+        let s2 = Series::new("Div Yield", &[9.0, 1.32, 4.0]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let s1 = Series::new("Symbol", &["ABM"]);
+        let s2 = Series::new("Div Yield", &[9.0]);
+
+        let ref_df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let result = screen_10_11_12(
+            &df,
+            &screen_parameters(min_divy, max_divy, 75.0, 10.0),
+            &["Min Div Yield", "Max Div Yield"],
+            "Div Yield",
+        )?;
+        assert!(result.frame_equal(&ref_df));
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_divy_max() -> Result<(), String> {
+        let max_divy = 10.0;
+        let min_divy = 3.0;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT"]);
+        let s2 = Series::new("Div Yield", &[11.0, 1.32, 4.0]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let s1 = Series::new("Symbol", &["CAT"]);
+        let s2 = Series::new("Div Yield", &[4.0]);
+
+        let ref_df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let result = screen_10_11_12(
+            &df,
+            &screen_parameters(min_divy, max_divy, 75.0, 10.0),
+            &["Min Div Yield", "Max Div Yield"],
+            "Div Yield",
+        )?;
+        assert!(result.frame_equal(&ref_df));
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_div_payout() -> Result<(), String> {
+        let max_payout_rate = 75.0;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32, 4.0]);
+        let s3 = Series::new("Current Div", &[0.54, 1.62, 0.14]);
+        let s4 = Series::new("CF/Share", &[10.0, 2.0, 20.0]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4]).unwrap();
+
+        let s1 = Series::new("Symbol", &["ABM", "CAT"]);
+        let s2 = Series::new("Div Yield", &[5.54, 4.0]);
+        let s3 = Series::new("Current Div", &[0.54, 0.14]);
+        let s4 = Series::new("CF/Share", &[10.0, 20.0]);
+
+        let ref_df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4]).unwrap();
+        let result = screen_10_11_12(
+            &df,
+            &screen_parameters(3.9, 10.0, max_payout_rate, 10.0),
+            &["Div Payout"],
+            "Div Yield",
+        )?;
+        assert!(result.frame_equal(&ref_df));
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_div_growth() -> Result<(), String> {
+        let min_growth_rate = 7.0;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32, 4.0]);
+        let s3 = Series::new("Current Div", &[0.54, 1.62, 0.14]);
+        let s4 = Series::new("CF/Share", &[10.0, 2.0, 20.0]);
+        let s5 = Series::new("DGR 1Y", &[7.05, 0.68, 3.94]);
+        let s6 = Series::new("DGR 3Y", &[8.51, 0.91, 3.07]);
+        let s7 = Series::new("DGR 5Y", &[8.96, 3.36, 5.29]);
+        let s8 = Series::new("DGR 10Y", &[8.87, 9.34, 4.97]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6, s7, s8]).unwrap();
+
+        let s1 = Series::new("Symbol", &["ABM"]);
+        let s2 = Series::new("Div Yield", &[5.54]);
+        let s3 = Series::new("Current Div", &[0.54]);
+        let s4 = Series::new("CF/Share", &[10.0]);
+        let s5 = Series::new("DGR 1Y", &[7.05]);
+        let s6 = Series::new("DGR 3Y", &[8.51]);
+        let s7 = Series::new("DGR 5Y", &[8.96]);
+        let s8 = Series::new("DGR 10Y", &[8.87]);
+        let ref_df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6, s7, s8]).unwrap();
+        let result = screen_10_11_12(
+            &df,
+            &screen_parameters(3.9, 10.0, 75.0, min_growth_rate),
+            &["DGR 5Y to 10Y", "Min DGR 1Y"],
+            "DGR 1Y",
+        )?;
+        assert!(result.frame_equal(&ref_df));
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_balance_sheet_health() -> Result<(), String> {
+        let thresholds = HealthThresholds {
+            max_debt_to_equity: Some(1.0),
+            min_current_ratio: Some(1.2),
+            ..Default::default()
+        };
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT", "XOM"]);
+        let s2 = Series::new("Debt/Capital", &[Some(0.4), Some(0.6), Some(0.2), None]);
+        let s3 = Series::new("Current R", &[Some(1.5), Some(2.0), Some(0.9), Some(1.3)]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3]).unwrap();
+
+        // INTC: D/E = 0.6/0.4 = 1.5, CAT: current ratio below 1.2,
+        // XOM: no Debt/Capital data so it is accepted
+        let result = analyze_balance_sheet_health(&df, &thresholds).unwrap();
+        let symbols = result.column("Symbol").unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols.get(0).unwrap(), AnyValue::Utf8("ABM"));
+        assert_eq!(symbols.get(1).unwrap(), AnyValue::Utf8("XOM"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_safety_score() -> Result<(), String> {
+        let min_safety_score = 50.0;
+
+        let s1 = Series::new("Symbol", &["CAT", "ABM"]);
+        let s2 = Series::new("Div Yield", &[11.0, 5.0]);
+        let s3 = Series::new("Annualized", &[2.0, 1.0]);
+        let s4 = Series::new("CF/Share", &[2.0, 2.5]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4]).unwrap();
+
+        // ABM: payout 40% (25 of 25 points), Div Yield 5% (10 of 10 points)
+        // CAT: payout 100% (0 of 25 points), Div Yield 11% (2 of 10 points)
+        let result = analyze_safety_score(&df, min_safety_score, None).unwrap();
+        assert_eq!(result.height(), 1);
+        assert_eq!(
+            result.column("Symbol").unwrap().get(0).unwrap(),
+            AnyValue::Utf8("ABM")
+        );
+        assert_eq!(
+            result.column("Safety Score").unwrap().get(0).unwrap(),
+            AnyValue::Float64(100.0)
+        );
+        assert_eq!(
+            result.column("Safety Grade").unwrap().get(0).unwrap(),
+            AnyValue::Utf8("A")
+        );
+
+        // Ordering of the screen is kept over Safety Score
+        let result = analyze_safety_score(&df, 0.0, Some("Div Yield")).unwrap();
+        assert_eq!(
+            result.column("Symbol").unwrap().get(0).unwrap(),
+            AnyValue::Utf8("CAT")
+        );
+        let result = analyze_safety_score(&df, 0.0, None).unwrap();
+        assert_eq!(
+            result.column("Symbol").unwrap().get(0).unwrap(),
+            AnyValue::Utf8("ABM")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_analyze_sectors() -> Result<(), String> {
+        let sector_filter = SectorFilter {
+            include: vec![],
+            exclude: vec![Sector::RealEstate],
+        };
+
+        let s1 = Series::new("Symbol", &["ABM", "O", "INTC", "XYZ"]);
+        let s2 = Series::new(
+            "Sector",
+            &["Industrials", "REITs", "Technology", "Something else"],
+        );
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let result = analyze_sectors(&df, &sector_filter).unwrap();
+        let ref_df = DataFrame::new(vec![
+            Series::new("Symbol", &["ABM", "INTC", "XYZ"]),
+            Series::new(
+                "Sector",
+                &["Industrials", "Information Technology", "Unknown"],
+            ),
+        ])
+        .unwrap();
+        assert!(result.frame_equal(&ref_df));
+
+        let sector_filter = SectorFilter {
+            include: vec![Sector::InformationTechnology],
+            exclude: vec![],
+        };
+        let result = analyze_sectors(&df, &sector_filter).unwrap();
+        assert_eq!(result.height(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_median() -> Result<(), String> {
+        assert_eq!(median(&mut vec![]), None);
+        assert_eq!(median(&mut vec![3.0, 1.0, 2.0]), Some(2.0));
+        assert_eq!(median(&mut vec![4.0, 1.0, 2.0, 3.0]), Some(2.5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_criteria() -> Result<(), String> {
+        let params = ScreenParameters {
+            sp500_divy: 1.61,
+            inflation: 3.4,
+            min_div_yield: 4.7,
+            max_div_yield: 10.0,
+            max_div_payout_rate: 75.0,
+            min_div_growth_rate: 10.0,
+        };
+        let criteria = Screen::preset("10-11-12")?.criteria(&params)?;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32, 4.5]);
+        let s3 = Series::new("Current Div", &[0.22, 0.365, 1.6]);
+        let s4 = Series::new("CF/Share", &[1.7, 1.5, 2.0]);
+        let s5 = Series::new("DGR 1Y", &[12.0, 10.0, 10.0]);
+        let s6 = Series::new("DGR 5Y", &[6.0, 5.0, 3.0]);
+        let s7 = Series::new("DGR 10Y", &[Some(5.0), Some(10.0), None]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6, s7]).unwrap();
+
+        let (outcomes, near_misses) = explain_criteria(&df, &criteria, 10.0).unwrap();
+
+        let ref_outcomes = DataFrame::new(vec![
+            Series::new("Symbol", &["ABM", "INTC", "CAT"]),
+            Series::new(
+                "Min Div Yield",
+                &["PASS 5.54", "FAIL 1.32", "NEAR MISS 4.50"],
+            ),
+            Series::new("Max Div Yield", &["PASS 5.54", "PASS 1.32", "PASS 4.50"]),
+            Series::new("Div Payout", &["PASS 0.13", "PASS 0.24", "NEAR MISS 0.80"]),
+            Series::new("DGR 5Y to 10Y", &["PASS 1.20", "FAIL 0.50", "NO DATA"]),
+            Series::new("Min DGR 1Y", &["PASS 12.00", "PASS 10.00", "PASS 10.00"]),
+            Series::new("Passed", &[true, false, false]),
+        ])
+        .unwrap();
+        assert!(outcomes.frame_equal(&ref_outcomes));
+
+        assert_eq!(near_misses.height(), 2);
+        assert_eq!(
+            near_misses.column("Criterion").unwrap().get(1).unwrap(),
+            AnyValue::Utf8("Div Payout")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ten_eleven_twelve_preset() -> Result<(), String> {
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT", "XOM", "O"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32, 4.0, 6.0, 5.5]);
+        let s3 = Series::new("Current Div", &[0.22, 0.365, 0.2, 0.5, 1.8]);
+        let s4 = Series::new("CF/Share", &[1.7, 1.5, 2.0, 2.0, 2.0]);
+        let s5 = Series::new("DGR 1Y", &[12.0, 10.0, 15.0, 11.0, 12.0]);
+        let s6 = Series::new("DGR 5Y", &[6.0, 5.0, 3.0, 2.0, 6.0]);
+        let s7 = Series::new("DGR 10Y", &[5.0, 10.0, 2.0, 4.0, 5.0]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6, s7]).unwrap();
+
+        // INTC yields too little, XOM dividend growth slows down and O pays out too much
+        let screen = Screen::preset("10-11-12")?;
+        let result = apply_criteria(
+            &df,
+            &screen.criteria(&screen_parameters(3.9, 10.0, 75.0, 10.0))?,
+            screen.sort_by.as_deref(),
+        )?;
+        let symbols: Vec<Option<&str>> = result
+            .column("Symbol")
+            .map_err(|e| e.to_string())?
+            .utf8()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+        assert_eq!(symbols, vec![Some("CAT"), Some("ABM")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ranking_factor_value() -> Result<(), String> {
+        let s1 = Series::new("Symbol", &["ABM", "INTC"]);
+        let s2 = Series::new("Annualized", &[1.0, 0.5]);
+        let s3 = Series::new("CF/Share", &[2.0, 0.0]);
+        let s4 = Series::new("Years of consecutive Div growth", &[Some(10u32), None]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4]).unwrap();
+
+        assert_eq!(
+            ranking_factor_value("payout", false)?.0.evaluate(&df),
+            vec![Some(0.5), None]
+        );
+        assert_eq!(
+            ranking_factor_value("streak", true)?.0.evaluate(&df),
+            vec![Some(10.0), None]
+        );
+        assert_eq!(
+            ranking_factor_value("valuation", true)?,
+            (CriterionValue::Column("P/E".to_owned()), true)
+        );
+        // Every factor has values for both sources of data
+        RANKING_FACTORS.iter().try_for_each(|(factor, _, _)| {
+            ranking_factor_value(factor, false)?;
+            ranking_factor_value(factor, true).map(|_| ())
+        })?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_snapshot_from_list() -> Result<(), String> {
+        let s1 = Series::new("Symbol", &["ABM", "INTC"]);
+        let s2 = Series::new("Price", &[40.0, 30.0]);
+        let s3 = Series::new("Current Div", &[0.22, 0.125]);
+        let s4 = Series::new("Annualized", &[0.88, 0.5]);
+        let s5 = Series::new("CF/Share", &[2.2, 1.0]);
+        let s6 = Series::new("No Years", &[56.0, 0.0]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6]).unwrap();
+
+        let snapshot = snapshot_from_list(&df, "ABM")?;
+        assert_eq!(snapshot.price, Some(40.0));
+        assert_eq!(snapshot.curr_div, Some(0.22));
+        assert_eq!(snapshot.payout_ratio.map(|p| p.round()), Some(10.0));
+        assert_eq!(snapshot.streak, Some(56));
+        // Missing columns are no data
+        assert_eq!(snapshot.div_yield, None);
+        assert!(snapshot_from_list(&df, "XOM").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_forecast_chart() -> Result<(), String> {
+        let args = Args::parse_from(["divanalysis", "--years", "1"]);
+        let s1 = Series::new("Symbol", &["ABM", "INTC"]);
+        let s2 = Series::new("Price", &[100.0, 30.0]);
+        let s3 = Series::new("Div Yield", &[Some(4.0), None]);
+        let s4 = Series::new("DGR 5Y", &[10.0, 5.0]);
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4]).unwrap();
+
+        let chart = forecast_chart(&df, &LIST_FORECAST_COLUMNS, &args)?;
+        // Company without Div Yield cannot be forecasted
+        assert_eq!(chart.lines.len(), 1);
+        assert_eq!(chart.lines[0].caption, "ABM");
+        // 100 shares, 4 quarterly pay-outs of 1$ per share, 15% tax
+        let last = chart.lines[0].points.last().unwrap();
+        assert_eq!(last.0, 1.0);
+        assert_eq!((last.1 * 100.0).round() / 100.0, 340.0);
+        Ok(())
+    }
 }
@@ -0,0 +1,487 @@
+use investments_forecasting::forecast::forecast_dividend_gains;
+use investments_forecasting::screening::{
+    apply_criteria, optional_f64_column, Screen, ScreenParameters,
+};
+use polars::prelude::*;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+use super::ForecastColumns;
+
+/// Error of a request: HTTP status and message
+type ApiError = (u16, &'static str);
+
+/// Forecast parameters used when not given in query
+#[derive(Debug, Clone)]
+pub struct ForecastDefaults {
+    pub capital: f64,
+    pub years: u32,
+    /// Annual share price growth rate[%]
+    pub share_price_growth_rate: f64,
+    /// Dividend tax rate[%]
+    pub tax_rate: f64,
+}
+
+/// JSON API over companies loaded once at start
+pub struct Api {
+    companies: DataFrame,
+    /// Screen used when none is given in query
+    screen: Screen,
+    params: ScreenParameters,
+    is_polygon: bool,
+    forecast_columns: &'static ForecastColumns,
+    forecast: ForecastDefaults,
+}
+
+/// Longest forecast[years] served, so that time line of days stays reasonably small
+const MAX_FORECAST_YEARS: f64 = 100.0;
+
+/// Decode percent-encoded query component, '+' stands for space
+fn decode_component(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = component
+            .get(i + 1..i + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_component(key), decode_component(value))
+        })
+        .collect()
+}
+
+fn query_value<'a>(query: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    query
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Number given in query. Infinite and NaN values are rejected
+fn query_f64(query: &[(String, String)], name: &str, default: f64) -> Result<f64, ApiError> {
+    match query_value(query, name) {
+        Some(value) => value
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| {
+                log::error!("Invalid value of {name}: {value}");
+                (400, "Query parameter is not a finite number")
+            }),
+        None => Ok(default),
+    }
+}
+
+fn to_json(df: &DataFrame) -> Result<Value, ApiError> {
+    let mut buffer: Vec<u8> = vec![];
+    JsonWriter::new(&mut buffer)
+        .with_json_format(JsonFormat::Json)
+        .finish(&mut df.clone())
+        .map_err(|e| {
+            log::error!("Unable to serialize companies: {e}");
+            (500, "Unable to serialize companies")
+        })?;
+    serde_json::from_slice(&buffer).map_err(|e| {
+        log::error!("Unable to serialize companies: {e}");
+        (500, "Unable to serialize companies")
+    })
+}
+
+impl Api {
+    pub fn new(
+        companies: DataFrame,
+        screen: Screen,
+        params: ScreenParameters,
+        is_polygon: bool,
+        forecast_columns: &'static ForecastColumns,
+        forecast: ForecastDefaults,
+    ) -> Self {
+        Api {
+            companies,
+            screen,
+            params,
+            is_polygon,
+            forecast_columns,
+            forecast,
+        }
+    }
+
+    /// Respond to request with HTTP status and JSON body
+    pub fn handle(&self, method: &Method, url: &str) -> (u16, Value) {
+        if *method != Method::Get {
+            return (405, json!({ "error": "Only GET requests are supported" }));
+        }
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query = parse_query(query);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let result = match segments.as_slice() {
+            [""] => Ok(json!({
+                "endpoints": [
+                    "/companies",
+                    "/companies/<symbol>",
+                    "/screen?preset=<name>&min_div_yield=<%>&max_div_yield=<%>&max_div_payout_rate=<%>&min_div_growth_rate=<%>",
+                    "/forecast?symbol=<symbol>&capital=<$>&years=<years>&share_price_growth_rate=<%>&tax_rate=<%>",
+                ]
+            })),
+            ["companies"] => to_json(&self.companies),
+            ["companies", symbol] => self.company(symbol),
+            ["screen"] => self.screen(&query),
+            ["forecast"] => self.forecast(&query),
+            _ => Err((404, "Unknown endpoint")),
+        };
+        match result {
+            Ok(body) => (200, body),
+            Err((status, message)) => (status, json!({ "error": message })),
+        }
+    }
+
+    fn company_row(&self, symbol: &str) -> Result<DataFrame, ApiError> {
+        let mask = self
+            .companies
+            .column("Symbol")
+            .map_err(|_| (500, "Symbol column does not exist!"))?
+            .equal(symbol.to_uppercase().as_str())
+            .map_err(|_| (500, "Unable to create mask"))?;
+        let df = self
+            .companies
+            .filter(&mask)
+            .map_err(|_| (500, "Error filtering"))?;
+        if df.height() == 0 {
+            log::error!("Company {symbol} is not loaded");
+            return Err((404, "Company is not loaded"));
+        }
+        Ok(df)
+    }
+
+    fn company(&self, symbol: &str) -> Result<Value, ApiError> {
+        let companies = to_json(&self.company_row(symbol)?)?;
+        Ok(companies[0].clone())
+    }
+
+    fn screen(&self, query: &[(String, String)]) -> Result<Value, ApiError> {
+        let screen = match query_value(query, "preset") {
+            // 10-11-12 system has its own variant for Polygon.io data
+            Some("10-11-12") if self.is_polygon => Screen::preset("10-11-12-polygon"),
+            Some(preset) => Screen::preset(preset),
+            None => Ok(self.screen.clone()),
+        }
+        .map_err(|e| (400, e))?;
+        let params = ScreenParameters {
+            sp500_divy: query_f64(query, "sp500_divy", self.params.sp500_divy)?,
+            inflation: query_f64(query, "inflation", self.params.inflation)?,
+            min_div_yield: query_f64(query, "min_div_yield", self.params.min_div_yield)?,
+            max_div_yield: query_f64(query, "max_div_yield", self.params.max_div_yield)?,
+            max_div_payout_rate: query_f64(
+                query,
+                "max_div_payout_rate",
+                self.params.max_div_payout_rate,
+            )?,
+            min_div_growth_rate: query_f64(
+                query,
+                "min_div_growth_rate",
+                self.params.min_div_growth_rate,
+            )?,
+        };
+        let criteria = screen.criteria(&params).map_err(|e| (400, e))?;
+        let shortlisted = apply_criteria(&self.companies, &criteria, screen.sort_by.as_deref())
+            .map_err(|e| (500, e))?;
+        let criteria: Vec<Value> = criteria
+            .iter()
+            .map(|c| {
+                json!({
+                    "name": c.name,
+                    "condition": format!("{} {} {:.2}", c.value, c.comparison.symbol(), c.threshold),
+                    "derivation": c.derivation,
+                })
+            })
+            .collect();
+        Ok(json!({
+            "screen": screen.name,
+            "criteria": criteria,
+            "companies": to_json(&shortlisted)?,
+        }))
+    }
+
+    /// Share price, Div Yield[%], DGR[%] and number of pay-outs a year of forecasted company.
+    /// Loaded company is used when symbol is given, otherwise values are taken from query
+    fn forecast_inputs(
+        &self,
+        query: &[(String, String)],
+    ) -> Result<(String, f64, f64, f64, u32), ApiError> {
+        match query_value(query, "symbol") {
+            Some(symbol) => {
+                let df = self.company_row(symbol)?;
+                let value = |name: &str| optional_f64_column(&df, name)[0];
+                let columns = self.forecast_columns;
+                match (
+                    value(columns.share_price),
+                    value(columns.div_yield),
+                    value(columns.dgr),
+                ) {
+                    (Some(price), Some(divy), Some(dgr)) if price > 0.0 => Ok((
+                        symbol.to_uppercase(),
+                        price,
+                        divy,
+                        dgr,
+                        columns
+                            .frequency
+                            .and_then(value)
+                            .map_or(4, |f| f as u32)
+                            .max(1),
+                    )),
+                    _ => Err((422, "No data to forecast dividend gains of the company")),
+                }
+            }
+            None => {
+                let required = |name: &str| match query_value(query, name) {
+                    Some(_) => query_f64(query, name, 0.0),
+                    None => Err((
+                        400,
+                        "Either symbol or price, div_yield and div_growth are required",
+                    )),
+                };
+                let frequency = query_f64(query, "frequency", 4.0)?;
+                if frequency > 365.0 {
+                    log::error!("Dividends paid {frequency} times a year requested");
+                    return Err((400, "Dividends can be paid at most daily"));
+                }
+                Ok((
+                    query_value(query, "name").unwrap_or("custom").to_owned(),
+                    required("price")?,
+                    required("div_yield")?,
+                    required("div_growth")?,
+                    frequency.max(1.0) as u32,
+                ))
+            }
+        }
+    }
+
+    fn forecast(&self, query: &[(String, String)]) -> Result<Value, ApiError> {
+        let (name, share_price, div_yield, div_growth, frequency) = self.forecast_inputs(query)?;
+        let capital = query_f64(query, "capital", self.forecast.capital)?;
+        let years = query_f64(query, "years", self.forecast.years as f64)?;
+        if years > MAX_FORECAST_YEARS {
+            log::error!("Forecast of {years} years requested");
+            return Err((400, "Forecast can be made for at most 100 years"));
+        }
+        let years = years.max(1.0) as u32;
+        let share_price_growth_rate = query_f64(
+            query,
+            "share_price_growth_rate",
+            self.forecast.share_price_growth_rate,
+        )?;
+        let tax_rate = query_f64(query, "tax_rate", self.forecast.tax_rate)?;
+
+        let time_line: Vec<u32> = (1..365 * years + 1).collect();
+        let (final_capital, last_payout, gains) = forecast_dividend_gains(
+            capital,
+            div_yield / 100.0,
+            div_growth / 100.0,
+            share_price,
+            share_price_growth_rate / 100.0,
+            tax_rate / 100.0,
+            &time_line,
+            frequency,
+        );
+        // Total dividends received by the end of every year
+        let yearly_dividends: Vec<f64> = (1..years as usize + 1)
+            .map(|year| gains[year * 365 - 1])
+            .collect();
+        Ok(json!({
+            "name": name,
+            "share_price": share_price,
+            "div_yield": div_yield,
+            "div_growth": div_growth,
+            "payouts_per_year": frequency,
+            "capital": capital,
+            "years": years,
+            "share_price_growth_rate": share_price_growth_rate,
+            "tax_rate": tax_rate,
+            "final_capital": final_capital,
+            "last_payout": last_payout,
+            "total_dividends": gains.last(),
+            "yearly_dividends": yearly_dividends,
+        }))
+    }
+}
+
+/// Serve API on localhost until the program is stopped
+pub fn serve(api: &Api, port: u16) -> Result<(), &'static str> {
+    let address = format!("127.0.0.1:{port}");
+    let server = Server::http(&address).map_err(|e| {
+        log::error!("Unable to listen on {address}: {e}");
+        "Error: Unable to start server"
+    })?;
+    println!("Serving analysis on http://{address}");
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .map_err(|_| "Error: Invalid Content-Type header")?;
+    for request in server.incoming_requests() {
+        let (status, body) = api.handle(request.method(), request.url());
+        log::info!("{} {} -> {status}", request.method(), request.url());
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            log::error!("Unable to respond: {e}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api() -> Result<Api, &'static str> {
+        let df = DataFrame::new(vec![
+            Series::new("Symbol", &["ABM", "ABR", "O"]),
+            Series::new("Price", &[100.0, 14.0, 50.0]),
+            Series::new("Div Yield", &[4.0, 6.0, 7.0]),
+            Series::new("Current Div", &[1.0, 1.0, 1.0]),
+            Series::new("CF/Share", &[2.0, 2.0, 1.2]),
+            Series::new("DGR 1Y", &[12.0, 11.0, 20.0]),
+            Series::new("DGR 5Y", &[10.0, 12.0, 12.0]),
+            Series::new("DGR 10Y", &[10.0, 10.0, 10.0]),
+        ])
+        .unwrap();
+        Ok(Api::new(
+            df,
+            Screen::preset("10-11-12")?,
+            ScreenParameters {
+                sp500_divy: 1.61,
+                inflation: 3.4,
+                min_div_yield: 4.7,
+                max_div_yield: 10.0,
+                max_div_payout_rate: 75.0,
+                min_div_growth_rate: 10.0,
+            },
+            false,
+            &super::super::LIST_FORECAST_COLUMNS,
+            ForecastDefaults {
+                capital: 10000.0,
+                years: 1,
+                share_price_growth_rate: 7.4,
+                tax_rate: 15.0,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_company() -> Result<(), String> {
+        let api = api()?;
+        let (status, body) = api.handle(&Method::Get, "/companies/abr");
+        assert_eq!(status, 200);
+        assert_eq!(body["Symbol"], json!("ABR"));
+        assert_eq!(body["Div Yield"], json!(6.0));
+
+        let (status, _) = api.handle(&Method::Get, "/companies/XOM");
+        assert_eq!(status, 404);
+        let (status, _) = api.handle(&Method::Post, "/companies/ABR");
+        assert_eq!(status, 405);
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen() -> Result<(), String> {
+        let api = api()?;
+        // ABM yields too little, O pays out too much
+        let (status, body) = api.handle(&Method::Get, "/screen");
+        assert_eq!(status, 200);
+        assert_eq!(body["screen"], json!("10-11-12"));
+        assert_eq!(body["companies"].as_array().map(|c| c.len()), Some(1));
+
+        let (_, body) = api.handle(
+            &Method::Get,
+            "/screen?min_div_yield=3.9&max_div_payout_rate=85",
+        );
+        assert_eq!(body["companies"].as_array().map(|c| c.len()), Some(3));
+
+        let (status, _) = api.handle(&Method::Get, "/screen?min_div_yield=high");
+        assert_eq!(status, 400);
+        let (status, _) = api.handle(&Method::Get, "/screen?preset=unknown");
+        assert_eq!(status, 400);
+        Ok(())
+    }
+
+    #[test]
+    fn test_forecast() -> Result<(), String> {
+        let api = api()?;
+        // 100 shares, 4 quarterly pay-outs of 1$ per share, 15% tax
+        let (status, body) = api.handle(&Method::Get, "/forecast?symbol=ABM");
+        assert_eq!(status, 200);
+        let total = body["total_dividends"].as_f64().unwrap();
+        assert_eq!((total * 100.0).round() / 100.0, 340.0);
+        assert_eq!(
+            body["yearly_dividends"].as_array().map(|y| y.len()),
+            Some(1)
+        );
+
+        let (status, body) = api.handle(
+            &Method::Get,
+            "/forecast?price=100&div_yield=4&div_growth=10&years=2&tax_rate=0",
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["name"], json!("custom"));
+        assert_eq!(
+            body["yearly_dividends"].as_array().map(|y| y.len()),
+            Some(2)
+        );
+
+        let (status, _) = api.handle(&Method::Get, "/forecast?price=100");
+        assert_eq!(status, 400);
+
+        // Years has to be a finite number of at most 100
+        let query = "/forecast?price=100&div_yield=4&div_growth=10&years=";
+        for years in ["inf", "NaN", "101"] {
+            let (status, _) = api.handle(&Method::Get, &format!("{query}{years}"));
+            assert_eq!(status, 400);
+        }
+        let (status, body) = api.handle(&Method::Get, &format!("{query}1%2E5"));
+        assert_eq!(status, 200);
+        assert_eq!(
+            body["yearly_dividends"].as_array().map(|y| y.len()),
+            Some(1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_query() -> Result<(), String> {
+        assert_eq!(
+            parse_query("name=Big+Blue%21&years=1%2E5&&flag"),
+            vec![
+                ("name".to_owned(), "Big Blue!".to_owned()),
+                ("years".to_owned(), "1.5".to_owned()),
+                ("flag".to_owned(), "".to_owned()),
+            ]
+        );
+        // Malformed escape is kept as is
+        assert_eq!(decode_component("100%"), "100%");
+        assert_eq!(decode_component("%zz"), "%zz");
+        Ok(())
+    }
+}
@@ -0,0 +1,535 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::ExecutableCommand;
+use investments_forecasting::export::cell_text;
+use investments_forecasting::screening::{apply_criteria, Criterion, Screen, ScreenParameters};
+use polars::prelude::*;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Sparkline, Table, TableState};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{stdout, Stdout};
+use std::process::{Command, Stdio};
+
+/// Thresholds that can be adjusted live and their step
+const THRESHOLDS: [(&str, f64); 4] = [
+    ("Min Div Yield[%]", 0.1),
+    ("Max Div Yield[%]", 0.5),
+    ("Max Div Payout[%]", 1.0),
+    ("Min Div Growth[%]", 0.5),
+];
+
+/// divforecasting plots at most that many companies at once
+const MAX_FORECAST_COMPANIES: usize = 6;
+
+const FORECAST_OUTPUT: &str = "dividend-investment-gains.png";
+
+const HELP: &str =
+    "Up/Down: select  Tab: threshold  +/-: adjust  s: sort column  r: reverse sort  \
+                    Space: mark  Enter: details  f: forecast  q: quit";
+
+/// Companies loaded once and screened live with adjustable thresholds
+pub struct Browser {
+    companies: DataFrame,
+    /// Columns presented in the table
+    columns: Vec<String>,
+    div_histories: Option<BTreeMap<String, Vec<(String, f64)>>>,
+    screen: Screen,
+    params: ScreenParameters,
+    criteria: Vec<Criterion>,
+    /// Companies passing current thresholds
+    shortlisted: DataFrame,
+    sort_column: usize,
+    sort_descending: bool,
+    threshold: usize,
+    table: TableState,
+    marked: BTreeSet<String>,
+    show_detail: bool,
+    /// Arguments of divforecasting other than companies
+    forecast_args: Vec<String>,
+    status: String,
+}
+
+impl Browser {
+    pub fn new(
+        companies: DataFrame,
+        columns: &[&str],
+        div_histories: Option<BTreeMap<String, Vec<(String, f64)>>>,
+        screen: Screen,
+        params: ScreenParameters,
+        forecast_args: Vec<String>,
+    ) -> Result<Self, &'static str> {
+        let columns: Vec<String> = columns
+            .iter()
+            .filter(|c| companies.column(c).is_ok())
+            .map(|c| c.to_string())
+            .collect();
+        if columns.is_empty() {
+            return Err("Error: No columns to browse");
+        }
+        let mut browser = Browser {
+            companies,
+            columns,
+            div_histories,
+            screen,
+            params,
+            criteria: vec![],
+            shortlisted: DataFrame::default(),
+            sort_column: 0,
+            sort_descending: false,
+            threshold: 0,
+            table: TableState::default(),
+            marked: BTreeSet::new(),
+            show_detail: false,
+            forecast_args,
+            status: "".to_owned(),
+        };
+        browser.refresh()?;
+        Ok(browser)
+    }
+
+    /// Screen companies with current thresholds and sort them
+    fn refresh(&mut self) -> Result<(), &'static str> {
+        self.criteria = self.screen.criteria(&self.params)?;
+        let column = self.columns[self.sort_column].as_str();
+        self.shortlisted = apply_criteria(&self.companies, &self.criteria, None)?
+            .sort([column], self.sort_descending, false)
+            .map_err(|_| {
+                log::error!("Could not sort along {column}");
+                "Error: Could not sort companies"
+            })?;
+        let selected = match self.shortlisted.height() {
+            0 => None,
+            height => Some(self.table.selected().unwrap_or(0).min(height - 1)),
+        };
+        self.table.select(selected);
+        Ok(())
+    }
+
+    fn threshold_mut(&mut self, threshold: usize) -> &mut f64 {
+        match threshold {
+            0 => &mut self.params.min_div_yield,
+            1 => &mut self.params.max_div_yield,
+            2 => &mut self.params.max_div_payout_rate,
+            _ => &mut self.params.min_div_growth_rate,
+        }
+    }
+
+    fn threshold_value(&self, threshold: usize) -> f64 {
+        match threshold {
+            0 => self.params.min_div_yield,
+            1 => self.params.max_div_yield,
+            2 => self.params.max_div_payout_rate,
+            _ => self.params.min_div_growth_rate,
+        }
+    }
+
+    /// Change currently selected threshold by given number of steps
+    fn adjust(&mut self, steps: f64) -> Result<(), &'static str> {
+        let (_, step) = THRESHOLDS[self.threshold];
+        let value = self.threshold_mut(self.threshold);
+        // Rounding keeps values from drifting e.g. 4.7 + 0.1 = 4.8000000001
+        *value = (((*value + steps * step) * 100.0).round() / 100.0).max(0.0);
+        self.refresh()
+    }
+
+    fn next_threshold(&mut self) {
+        self.threshold = (self.threshold + 1) % THRESHOLDS.len();
+    }
+
+    fn next_sort_column(&mut self) -> Result<(), &'static str> {
+        self.sort_column = (self.sort_column + 1) % self.columns.len();
+        self.refresh()
+    }
+
+    fn reverse_sort(&mut self) -> Result<(), &'static str> {
+        self.sort_descending = !self.sort_descending;
+        self.refresh()
+    }
+
+    fn select_next(&mut self) {
+        if let Some(i) = self.table.selected() {
+            self.table.select(Some(
+                (i + 1).min(self.shortlisted.height().saturating_sub(1)),
+            ));
+        }
+    }
+
+    fn select_previous(&mut self) {
+        if let Some(i) = self.table.selected() {
+            self.table.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    fn selected_symbol(&self) -> Option<String> {
+        let i = self.table.selected()?;
+        match self.shortlisted.column("Symbol").ok()?.get(i).ok()? {
+            AnyValue::Utf8(symbol) => Some(symbol.to_owned()),
+            _ => None,
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(symbol) = self.selected_symbol() {
+            if !self.marked.remove(&symbol) {
+                self.marked.insert(symbol);
+            }
+        }
+    }
+
+    /// Companies to be forecasted: marked ones or selected one when none is marked
+    fn forecast_targets(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            self.selected_symbol().into_iter().collect()
+        } else {
+            self.marked.iter().cloned().collect()
+        }
+    }
+
+    /// Run divforecasting projection of marked companies
+    fn forecast(&mut self) {
+        let targets = self.forecast_targets();
+        if targets.is_empty() {
+            self.status = "No company to forecast".to_owned();
+            return;
+        }
+        if targets.len() > MAX_FORECAST_COMPANIES {
+            self.status =
+                format!("At most {MAX_FORECAST_COMPANIES} companies can be forecasted at once");
+            return;
+        }
+        let mut command = divforecasting_command();
+        command
+            .args(&self.forecast_args)
+            .args(["--output", FORECAST_OUTPUT])
+            .args(targets.iter().flat_map(|t| ["--company", t.as_str()]))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        self.status = match command.status() {
+            Ok(status) if status.success() => format!(
+                "Forecast of {} written to {FORECAST_OUTPUT}",
+                targets.join(", ")
+            ),
+            Ok(status) => format!("divforecasting failed ({status})"),
+            Err(e) => {
+                log::error!("Unable to run divforecasting: {e}");
+                "Unable to run divforecasting".to_owned()
+            }
+        };
+    }
+}
+
+/// divforecasting built next to this program, or found in PATH
+fn divforecasting_command() -> Command {
+    let name = format!("divforecasting{}", std::env::consts::EXE_SUFFIX);
+    let sibling = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(&name)))
+        .filter(|path| path.exists());
+    match sibling {
+        Some(path) => Command::new(path),
+        None => Command::new(name),
+    }
+}
+
+fn draw_thresholds(frame: &mut Frame, area: Rect, browser: &Browser) {
+    let thresholds: Vec<Span> = THRESHOLDS
+        .iter()
+        .enumerate()
+        .map(|(i, (title, _))| {
+            let text = format!(" {title}: {:.2} ", browser.threshold_value(i));
+            if i == browser.threshold {
+                Span::styled(text, Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                Span::raw(text)
+            }
+        })
+        .collect();
+    let criteria: Vec<String> = browser
+        .criteria
+        .iter()
+        .map(|c| format!("{} {} {:.2}", c.value, c.comparison.symbol(), c.threshold))
+        .collect();
+    let paragraph = Paragraph::new(vec![
+        Line::from(thresholds),
+        Line::from(criteria.join("; ")),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Thresholds ({})", browser.screen.name)),
+    );
+    frame.render_widget(paragraph, area);
+}
+
+fn draw_table(frame: &mut Frame, area: Rect, browser: &mut Browser) {
+    let df = &browser.shortlisted;
+    let mut widths: Vec<usize> = browser.columns.iter().map(|c| c.len() + 2).collect();
+    let rows: Vec<Row> = (0..df.height())
+        .map(|i| {
+            let cells: Vec<String> = browser
+                .columns
+                .iter()
+                .zip(widths.iter_mut())
+                .map(|(column, width)| {
+                    let text = df
+                        .column(column)
+                        .ok()
+                        .and_then(|c| c.get(i).ok())
+                        .map(|v| cell_text(&v))
+                        .unwrap_or_default();
+                    *width = (*width).max(text.len()).min(30);
+                    text
+                })
+                .collect();
+            let is_marked = cells
+                .first()
+                .map_or(false, |symbol| browser.marked.contains(symbol));
+            let row = Row::new(cells);
+            if is_marked {
+                row.style(Style::default().fg(Color::Green))
+            } else {
+                row
+            }
+        })
+        .collect();
+    let header: Vec<String> = browser
+        .columns
+        .iter()
+        .enumerate()
+        .map(
+            |(i, column)| match (i == browser.sort_column, browser.sort_descending) {
+                (true, false) => format!("{column} ^"),
+                (true, true) => format!("{column} v"),
+                (false, _) => column.clone(),
+            },
+        )
+        .collect();
+    let table = Table::new(rows, widths.iter().map(|w| Constraint::Length(*w as u16)))
+        .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            "Companies {}/{} (marked: {})",
+            df.height(),
+            browser.companies.height(),
+            browser.marked.len()
+        )))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, area, &mut browser.table);
+}
+
+fn draw_detail(frame: &mut Frame, area: Rect, browser: &Browser) {
+    let symbol = browser.selected_symbol().unwrap_or_default();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(8)])
+        .split(area);
+
+    let lines: Vec<Line> = match browser.table.selected() {
+        Some(i) => browser
+            .shortlisted
+            .get_columns()
+            .iter()
+            .map(|c| {
+                let text = c.get(i).map(|v| cell_text(&v)).unwrap_or_default();
+                Line::from(format!("{}: {text}", c.name()))
+            })
+            .collect(),
+        None => vec![],
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(symbol.clone())),
+        chunks[0],
+    );
+
+    let history = browser
+        .div_histories
+        .as_ref()
+        .and_then(|histories| histories.get(&symbol));
+    match history {
+        Some(history) if !history.is_empty() => {
+            // Sparkline presents integers, so dividends are given in tenths of a cent
+            let data: Vec<u64> = history
+                .iter()
+                .map(|(_, div)| (div * 1000.0).round() as u64)
+                .collect();
+            let title = format!(
+                "Dividend history {} .. {}",
+                history[0].0,
+                history[history.len() - 1].0
+            );
+            frame.render_widget(
+                Sparkline::default()
+                    .data(&data)
+                    .style(Style::default().fg(Color::Green))
+                    .block(Block::default().borders(Borders::ALL).title(title)),
+                chunks[1],
+            );
+        }
+        _ => frame.render_widget(
+            Paragraph::new(
+                "Dividend history is available only for companies fetched from Polygon.io",
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Dividend history"),
+            ),
+            chunks[1],
+        ),
+    }
+}
+
+fn draw(frame: &mut Frame, browser: &mut Browser) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(frame.size());
+    draw_thresholds(frame, chunks[0], browser);
+
+    if browser.show_detail {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[1]);
+        draw_table(frame, panes[0], browser);
+        draw_detail(frame, panes[1], browser);
+    } else {
+        draw_table(frame, chunks[1], browser);
+    }
+
+    let status = if browser.status.is_empty() {
+        HELP
+    } else {
+        browser.status.as_str()
+    };
+    frame.render_widget(Paragraph::new(status), chunks[2]);
+}
+
+fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    browser: &mut Browser,
+) -> Result<(), &'static str> {
+    loop {
+        terminal
+            .draw(|frame| draw(frame, browser))
+            .map_err(|_| "Error: Unable to draw terminal UI")?;
+        let key = match event::read().map_err(|_| "Error: Unable to read terminal event")? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        browser.status.clear();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => browser.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => browser.select_previous(),
+            KeyCode::Tab => browser.next_threshold(),
+            KeyCode::Char('+') | KeyCode::Char('=') => browser.adjust(1.0)?,
+            KeyCode::Char('-') => browser.adjust(-1.0)?,
+            KeyCode::Char('s') => browser.next_sort_column()?,
+            KeyCode::Char('r') => browser.reverse_sort()?,
+            KeyCode::Char(' ') => browser.toggle_mark(),
+            KeyCode::Enter => browser.show_detail = !browser.show_detail,
+            KeyCode::Char('f') => browser.forecast(),
+            _ => {}
+        }
+    }
+}
+
+/// Run terminal UI until user quits
+pub fn run(mut browser: Browser) -> Result<(), &'static str> {
+    enable_raw_mode().map_err(|_| "Error: Unable to enable raw mode of terminal")?;
+    stdout()
+        .execute(EnterAlternateScreen)
+        .map_err(|_| "Error: Unable to enter alternate screen")?;
+    let result = Terminal::new(CrosstermBackend::new(stdout()))
+        .map_err(|_| "Error: Unable to create terminal")
+        .and_then(|mut terminal| event_loop(&mut terminal, &mut browser));
+    // Terminal is restored whatever the outcome was
+    disable_raw_mode().map_err(|_| "Error: Unable to disable raw mode of terminal")?;
+    stdout()
+        .execute(LeaveAlternateScreen)
+        .map_err(|_| "Error: Unable to leave alternate screen")?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn browser() -> Result<Browser, &'static str> {
+        let df = DataFrame::new(vec![
+            Series::new("Symbol", &["ABM", "ABR", "O"]),
+            Series::new("Div Yield", &[5.0, 6.0, 7.0]),
+            Series::new("Current Div", &[1.0, 1.0, 1.0]),
+            Series::new("CF/Share", &[2.0, 2.0, 1.2]),
+            Series::new("DGR 1Y", &[12.0, 11.0, 20.0]),
+            Series::new("DGR 5Y", &[12.0, 12.0, 12.0]),
+            Series::new("DGR 10Y", &[10.0, 10.0, 10.0]),
+        ])
+        .unwrap();
+        Browser::new(
+            df,
+            &["Symbol", "Company", "Div Yield", "DGR 1Y"],
+            None,
+            Screen::preset("10-11-12")?,
+            ScreenParameters {
+                sp500_divy: 1.61,
+                inflation: 3.4,
+                min_div_yield: 4.7,
+                max_div_yield: 10.0,
+                max_div_payout_rate: 75.0,
+                min_div_growth_rate: 10.0,
+            },
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_browser_thresholds() -> Result<(), String> {
+        let mut browser = browser()?;
+        // Missing columns are not presented
+        assert_eq!(browser.columns, vec!["Symbol", "Div Yield", "DGR 1Y"]);
+        // O pays out 83% of cash flow
+        assert_eq!(browser.shortlisted.height(), 2);
+
+        // Min Div Yield: 4.7 -> 5.5
+        browser.adjust(8.0)?;
+        assert_eq!(browser.params.min_div_yield, 5.5);
+        assert_eq!(browser.selected_symbol(), Some("ABR".to_owned()));
+
+        // Max Div Payout: 75 -> 85
+        browser.next_threshold();
+        browser.next_threshold();
+        browser.adjust(10.0)?;
+        assert_eq!(browser.shortlisted.height(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_browser_sort_and_mark() -> Result<(), String> {
+        let mut browser = browser()?;
+        // Div Yield, descending
+        browser.next_sort_column()?;
+        browser.reverse_sort()?;
+        assert_eq!(browser.selected_symbol(), Some("ABR".to_owned()));
+        assert_eq!(browser.forecast_targets(), vec!["ABR".to_owned()]);
+
+        browser.toggle_mark();
+        browser.select_next();
+        browser.select_next();
+        assert_eq!(browser.selected_symbol(), Some("ABM".to_owned()));
+        browser.toggle_mark();
+        assert_eq!(
+            browser.forecast_targets(),
+            vec!["ABM".to_owned(), "ABR".to_owned()]
+        );
+        browser.toggle_mark();
+        assert_eq!(browser.forecast_targets(), vec!["ABR".to_owned()]);
+        Ok(())
+    }
+}
@@ -1,6 +1,21 @@
 use calamine::{open_workbook, Xlsx};
+use chrono::{Datelike, NaiveDate, Utc};
 use clap::Parser;
-use gnuplot::{AxesCommon, Caption, Color, Coordinate, Figure};
+use gnuplot::{
+    Axes2D, AxesCommon, Caption, Color, Coordinate, DashType, Figure, FillAlpha, LineStyle,
+};
+use investments_forecasting::backtest::{self, PriceHistory};
+use investments_forecasting::forecast::{
+    forecast_dividends_with_rates, to_real_terms, Contributions, Forecast, ForecastParams,
+    Reinvestment,
+};
+use investments_forecasting::growthmodel::GrowthModel;
+use investments_forecasting::inflation::CpiSeries;
+use investments_forecasting::montecarlo::{self, Bands, Distribution, MonteCarloParams};
+use investments_forecasting::portfolio::{
+    aggregate, forecast_portfolio, Allocation, Holding, PortfolioForecast, Strategy,
+};
+use investments_forecasting::tax::{tax_leakage, tax_paid, DividendTax, TaxModel};
 use polars::prelude::*;
 
 // TODO: frequency of div paid should be yield based on historical data not fixed to four
@@ -51,6 +66,147 @@ struct Args {
 
     #[arg(long, default_value_t = 15.0)]
     tax_rate: f64,
+
+    /// CSV file with CPI series ("Date" and either "CPI" index levels or "Inflation" year over
+    /// year rates[%]). Dividend gains are additionally presented in real terms
+    #[arg(long)]
+    cpi: Option<String>,
+
+    /// Dividend reinvestment (DRIP). Possible values: "none" (dividends collected as cash),
+    /// "fractional" (dividends buy fractional shares), "whole" (dividends buy whole shares,
+    /// leftover cash is carried forward)
+    #[arg(long, default_value = "none")]
+    drip: String,
+
+    /// Amount[$] invested periodically (dollar-cost averaging) at projected share price
+    #[arg(long)]
+    contribution: Option<f64>,
+
+    /// Frequency of contributions. Possible values: "monthly", "quarterly", "annual"
+    #[arg(long, default_value = "monthly")]
+    contribution_frequency: String,
+
+    /// Annual growth rate[%] of contribution amount
+    #[arg(long, default_value_t = 0.0)]
+    contribution_growth: f64,
+
+    /// Number of Monte Carlo paths. Annual share price growth and dividend growth are drawn
+    /// from normal distributions and percentile bands are plotted for every company
+    #[arg(long)]
+    monte_carlo: Option<usize>,
+
+    /// Standard deviation[%] of annual share price growth (Monte Carlo). If not given it is
+    /// estimated from closing prices of --prices or 15% is used
+    #[arg(long)]
+    price_volatility: Option<f64>,
+
+    /// Standard deviation[%] of annual dividend growth (Monte Carlo). If not given it is
+    /// estimated from dividend history (Polygon.io) or 5% is used
+    #[arg(long)]
+    dgr_volatility: Option<f64>,
+
+    /// Correlation (-1 to 1) of annual share price growth and dividend growth (Monte Carlo)
+    #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+    correlation: f64,
+
+    /// Annual dividend income[$] (after tax) which probability of reaching is reported (Monte Carlo)
+    #[arg(long)]
+    target_income: Option<f64>,
+
+    /// Seed of random generator (Monte Carlo)
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Dividend growth model, either for all companies or for one given as SYMBOL=MODEL (rates[%]).
+    /// Possible values: "constant", "linear-fade:<long term rate>:<years>",
+    /// "exp-fade:<long term rate>:<half life years>", "step:<rate>x<years>,...,<final rate>",
+    /// "regression" (fitted into dividend history), "yield-constant" (share price grows as dividends)
+    #[arg(long)]
+    growth_model: Vec<String>,
+
+    /// Backtest forecast of companies as if it was made at given past date (YYYY-MM-DD) and
+    /// report its error against actual dividends of every elapsed year (at most --years)
+    #[arg(long)]
+    backtest: Option<String>,
+
+    /// CSV file with closing prices ("Symbol", "Date" and "Close" columns) required by backtest
+    /// for share price at as of date and actual share prices, and by Monte Carlo to estimate mean
+    /// and standard deviation of annual share price growth
+    #[arg(long)]
+    prices: Option<String>,
+
+    /// Forecast companies as a single portfolio: --capital is split across them (equally unless
+    /// --weight or --shares is given) and combined income and value are plotted
+    #[arg(long)]
+    portfolio: bool,
+
+    /// Allocation weight of a portfolio holding given as SYMBOL=WEIGHT (custom company is
+    /// referred by its --custom-name)
+    #[arg(long)]
+    weight: Vec<String>,
+
+    /// Number of shares of a portfolio holding given as SYMBOL=SHARES
+    #[arg(long)]
+    shares: Vec<String>,
+
+    /// Strategy of directing dividends of portfolio holdings, strategies given are compared
+    /// (requires --portfolio). Possible values: "payer" (reinvest into the payer),
+    /// "highest-yield", "underweight" (redirect to holding most below its weight),
+    /// "rebalance[:<years>]" (reinvest into the payer and rebalance to weights), "cash"
+    #[arg(long)]
+    strategy: Vec<String>,
+
+    /// Residence of investor (PL, US, UK, DE). Instead of --tax-rate dividends are taxed by
+    /// withholding tax of company's domicile and top-up tax of residence
+    #[arg(long)]
+    residence: Option<String>,
+
+    /// Domicile of a company given as SYMBOL=COUNTRY (US, CA, UK, IE, NL, DE, FR, CH, PL).
+    /// Companies are domiciled in US unless given
+    #[arg(long)]
+    domicile: Vec<String>,
+
+    /// No W-8BEN form is filed, so US companies withhold 30% instead of treaty 15%
+    #[arg(long)]
+    no_w8ben: bool,
+
+    /// Companies domiciled outside US withhold treaty rate (relief at source) or withholding
+    /// above it is refunded. Statutory rate is withheld otherwise
+    #[arg(long)]
+    treaty_relief: bool,
+}
+
+/// Standard deviation[%] of annual dividend growth used when it is neither given nor
+/// estimated from dividend history
+const DEFAULT_DGR_VOLATILITY: f64 = 5.0;
+/// Standard deviation[%] of annual share price growth used by Monte Carlo when it is neither
+/// given nor estimated from price history
+const DEFAULT_PRICE_VOLATILITY: f64 = 15.0;
+
+/// Growth model of a company: the one given for its symbol or else the one given for all companies
+fn growth_model_of(specs: &[String], name: &str) -> Result<GrowthModel, &'static str> {
+    let company_spec = specs.iter().find_map(|spec| {
+        spec.split_once('=')
+            .filter(|(symbol, _)| symbol.trim().eq_ignore_ascii_case(name))
+            .map(|(_, model)| model)
+    });
+    let default_spec = specs.iter().rev().find(|spec| !spec.contains('='));
+    match company_spec.or(default_spec.map(|spec| spec.as_str())) {
+        Some(spec) => GrowthModel::from_spec(spec),
+        None => Ok(GrowthModel::Constant),
+    }
+}
+
+/// Domicile of a company given as SYMBOL=COUNTRY, US by default
+fn domicile_of(specs: &[String], name: &str) -> String {
+    specs
+        .iter()
+        .find_map(|spec| {
+            spec.split_once('=')
+                .filter(|(symbol, _)| symbol.trim().eq_ignore_ascii_case(name))
+                .map(|(_, country)| country.trim().to_owned())
+        })
+        .unwrap_or("US".to_owned())
 }
 
 enum Target<'a> {
@@ -58,16 +214,6 @@ enum Target<'a> {
     symbol(&'a str),
 }
 
-fn compute_dividend_gain(
-    num_shares: f64,
-    curr_div: f64,
-    num_capitalizations: u32,
-    transaction_tax: f64,
-) -> f64 {
-    let gains: f64 = num_shares * curr_div / (num_capitalizations as f64) * (1.0 - transaction_tax);
-    gains
-}
-
 fn compute_gain(
     capital: f64,
     investment_rate: f64,
@@ -238,20 +384,413 @@ fn forecast_low_risk_instruments(base_capital: f64) {
     fg.show().expect("Error plotting");
 }
 
-fn forecast_dividend_stocks(
+/// Plot dividend gains discounted by inflation with dashed line of company's color
+fn plot_real_terms(
+    axes: &mut Axes2D,
+    name: &str,
+    time_data: &Vec<u32>,
+    gains: &[f64],
+    inflation_path: &[f64],
+    color: &str,
+) {
+    let real_gains = to_real_terms(gains, time_data, inflation_path);
+    let caption = format!(
+        "{name} in real terms (Total Dividends Gains[$]: {:.2} )",
+        real_gains.last().unwrap_or(&0.0)
+    );
+    axes.lines(
+        time_data,
+        &real_gains,
+        &[Caption(&caption), Color(color), LineStyle(DashType::Dash)],
+    );
+}
+
+/// Name of additional chart e.g. gains.png -> gains-portfolio.png
+fn suffixed_output_name(output_file_name: &str, suffix: &str) -> String {
+    match output_file_name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{stem}-{suffix}.{extension}"),
+        None => format!("{output_file_name}-{suffix}"),
+    }
+}
+
+/// Plot median and 5/25/75/95 percentile bands in given color
+fn plot_bands(axes: &mut Axes2D, days: &[u32], bands: &Bands, color: &str) {
+    axes.fill_between(
+        days,
+        &bands.p5,
+        &bands.p95,
+        &[Caption("5-95 percentile"), Color(color), FillAlpha(0.2)],
+    );
+    axes.fill_between(
+        days,
+        &bands.p25,
+        &bands.p75,
+        &[Caption("25-75 percentile"), Color(color), FillAlpha(0.4)],
+    );
+    axes.lines(days, &bands.p50, &[Caption("median"), Color(color)]);
+}
+
+/// Plot percentile bands of total dividends and value of shares held simulated for a company
+fn plot_monte_carlo(
     output_file_name: &str,
-    base_capital: f64,
-    data: Option<String>,
-    companies: Vec<Target>,
-    investment_years: u32,
-    shares_price_growth_rate: f64,
-    tax_rate: f64,
+    name: &str,
+    simulation: &montecarlo::MonteCarloForecast,
+    monte_carlo: &MonteCarloParams,
+    target_income: Option<f64>,
+) {
+    let mut fg = Figure::new();
+    fg.set_terminal("pngcairo size 1280,960", output_file_name);
+
+    let median = |bands: &Bands| *bands.p50.last().unwrap_or(&0.0);
+    let mut title = format!(
+        "{name}: {} paths (price growth[%]: {:.2}±{:.2}, DGR[%]: {:.2}±{:.2}, correlation: {:.2}), median Total Dividends Gains[$]: {:.2}",
+        monte_carlo.num_paths,
+        monte_carlo.price_growth.mean * 100.0,
+        monte_carlo.price_growth.std_dev * 100.0,
+        monte_carlo.div_growth.mean * 100.0,
+        monte_carlo.div_growth.std_dev * 100.0,
+        monte_carlo.correlation,
+        median(&simulation.gains)
+    );
+    if let Some(target_income) = target_income {
+        title += &format!(
+            "\nProbability of Annual Income[$] >= {target_income:.2}: {:.1}%",
+            simulation.probability_of_income(target_income) * 100.0
+        );
+    }
+
+    let axes = fg
+        .axes2d()
+        .set_pos_grid(2, 1, 0)
+        .set_title(&title, &[gnuplot::LabelOption::<&str>::Font("Arial", 13.0)])
+        .set_x_label(
+            "time[days]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        )
+        .set_y_label(
+            "Total Dividends",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        );
+    plot_bands(axes, &simulation.days, &simulation.gains, "blue");
+
+    let axes = fg
+        .axes2d()
+        .set_pos_grid(2, 1, 1)
+        .set_title(
+            &format!(
+                "Portfolio value (median[$]: {:.2})",
+                median(&simulation.value)
+            ),
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 15.0)],
+        )
+        .set_x_label(
+            "time[days]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        )
+        .set_y_label(
+            "Value[$]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        );
+    plot_bands(axes, &simulation.days, &simulation.value, "web-green");
+
+    fg.show().expect("Error plotting");
+}
+
+/// Plot value of shares held and annual dividend income of every company over time together
+/// with combined ones of portfolio if companies are held together
+fn plot_portfolio(
+    output_file_name: &str,
+    time_data: &Vec<u32>,
+    forecasts: &[(String, Forecast)],
+    portfolio: Option<&PortfolioForecast>,
+) {
+    // Share of a holding in portfolio's final value or income
+    let share_of = |contributions: Option<&Vec<(String, f64)>>, i: usize| {
+        contributions
+            .and_then(|c| c.get(i))
+            .map_or("".to_owned(), |(_, share)| {
+                format!(", Share[%]: {share:.2}")
+            })
+    };
+    let colors: Vec<&str> = vec!["blue", "green", "navy", "web-green", "#127cc1", "#76B900"];
+    let mut fg = Figure::new();
+    fg.set_terminal("pngcairo size 1280,960", output_file_name);
+
+    let axes = fg
+        .axes2d()
+        .set_pos_grid(2, 1, 0)
+        .set_title(
+            "Portfolio value",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 15.0)],
+        )
+        .set_x_label(
+            "time[days]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        )
+        .set_y_label(
+            "Value[$]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        );
+    forecasts
+        .iter()
+        .enumerate()
+        .for_each(|(i, (name, forecast))| {
+            let caption = format!(
+                "{name} (Shares: {:.2}, Value[$]: {:.2}{})",
+                forecast.shares.last().unwrap_or(&0.0),
+                forecast.final_value,
+                share_of(portfolio.map(|p| &p.value_contributions), i)
+            );
+            axes.lines(
+                time_data,
+                &forecast.value,
+                &[Caption(&caption), Color(colors[i % colors.len()])],
+            );
+            if forecast.cost_basis.last() != forecast.cost_basis.first() {
+                let caption = format!(
+                    "{name} cost basis (Invested[$]: {:.2})",
+                    forecast.cost_basis.last().unwrap_or(&0.0)
+                );
+                axes.lines(
+                    time_data,
+                    &forecast.cost_basis,
+                    &[
+                        Caption(&caption),
+                        Color(colors[i % colors.len()]),
+                        LineStyle(DashType::Dash),
+                    ],
+                );
+            }
+        });
+    if let Some(portfolio) = portfolio {
+        let caption = format!(
+            "Portfolio (Value[$]: {:.2}, Invested[$]: {:.2})",
+            portfolio.value.last().unwrap_or(&0.0),
+            portfolio.cost_basis.last().unwrap_or(&0.0)
+        );
+        axes.lines(
+            time_data,
+            &portfolio.value,
+            &[Caption(&caption), Color("black")],
+        );
+    }
+
+    let axes = fg
+        .axes2d()
+        .set_pos_grid(2, 1, 1)
+        .set_title(
+            "Annual dividend income (after tax)",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 15.0)],
+        )
+        .set_x_label(
+            "time[days]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        )
+        .set_y_label(
+            "Income[$]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        );
+    forecasts
+        .iter()
+        .enumerate()
+        .for_each(|(i, (name, forecast))| {
+            let caption = format!(
+                "{name} (Annual Income[$]: {:.2}{})",
+                forecast.income.last().unwrap_or(&0.0),
+                share_of(portfolio.map(|p| &p.income_contributions), i)
+            );
+            axes.lines(
+                time_data,
+                &forecast.income,
+                &[Caption(&caption), Color(colors[i % colors.len()])],
+            );
+        });
+    if let Some(portfolio) = portfolio {
+        let caption = format!(
+            "Portfolio (Annual Income[$]: {:.2})",
+            portfolio.income.last().unwrap_or(&0.0)
+        );
+        axes.lines(
+            time_data,
+            &portfolio.income,
+            &[Caption(&caption), Color("black")],
+        );
+    }
+
+    fg.show().expect("Error plotting");
+}
+
+/// Forecast portfolio with every strategy, print and plot their value and annual income
+fn compare_strategies(
+    output_file_name: &str,
+    time_data: &Vec<u32>,
+    holdings: &[Holding],
+    strategies: &[Strategy],
 ) {
+    let colors: Vec<&str> = vec!["blue", "green", "navy", "web-green", "#127cc1", "#76B900"];
+    let results: Vec<(String, PortfolioForecast)> = strategies
+        .iter()
+        .map(|strategy| {
+            let forecasts = forecast_portfolio(holdings, time_data, *strategy);
+            (strategy.to_string(), aggregate(&forecasts))
+        })
+        .collect();
+
+    println!("Dividend strategies after {} days:", time_data.len());
+    results.iter().for_each(|(strategy, portfolio)| {
+        println!(
+            "   {strategy}: Annual Income[$]: {:.2}, Value[$]: {:.2}, Total Dividends Gains[$]: {:.2}",
+            portfolio.income.last().unwrap_or(&0.0),
+            portfolio.value.last().unwrap_or(&0.0),
+            portfolio.gains.last().unwrap_or(&0.0)
+        )
+    });
+
+    let mut fg = Figure::new();
+    fg.set_terminal("pngcairo size 1280,960", output_file_name);
+    let axes = fg
+        .axes2d()
+        .set_pos_grid(2, 1, 0)
+        .set_title(
+            "Portfolio value by dividend strategy",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 15.0)],
+        )
+        .set_x_label(
+            "time[days]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        )
+        .set_y_label(
+            "Value[$]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        );
+    results
+        .iter()
+        .enumerate()
+        .for_each(|(i, (strategy, portfolio))| {
+            let caption = format!(
+                "{strategy} (Value[$]: {:.2})",
+                portfolio.value.last().unwrap_or(&0.0)
+            );
+            axes.lines(
+                time_data,
+                &portfolio.value,
+                &[Caption(&caption), Color(colors[i % colors.len()])],
+            );
+        });
+
+    let axes = fg
+        .axes2d()
+        .set_pos_grid(2, 1, 1)
+        .set_title(
+            "Annual dividend income (after tax) by dividend strategy",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 15.0)],
+        )
+        .set_x_label(
+            "time[days]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        )
+        .set_y_label(
+            "Income[$]",
+            &[gnuplot::LabelOption::<&str>::Font("Arial", 12.0)],
+        );
+    results
+        .iter()
+        .enumerate()
+        .for_each(|(i, (strategy, portfolio))| {
+            let caption = format!(
+                "{strategy} (Annual Income[$]: {:.2})",
+                portfolio.income.last().unwrap_or(&0.0)
+            );
+            axes.lines(
+                time_data,
+                &portfolio.income,
+                &[Caption(&caption), Color(colors[i % colors.len()])],
+            );
+        });
+
+    fg.show().expect("Error plotting");
+}
+
+fn forecast_dividend_stocks(args: &Args, companies: Vec<Target>, inflation_path: Option<Vec<f64>>) {
+    let output_file_name: &str = &args.output;
+    let base_capital = args.capital;
+    let data = args.data.clone();
+    let investment_years = args.years;
     let time_data: Vec<u32> = (1u32..365 * investment_years + 1).collect();
 
-    let tax_rate = tax_rate / 100.0;
-    let mut num_capitalizations: u32 = 4;
-    let shares_price_growth_rate = shares_price_growth_rate / 100.0;
+    let tax_rate = args.tax_rate / 100.0;
+    let shares_price_growth_rate = args.share_price_growth_rate / 100.0;
+    let reinvestment = Reinvestment::from_name(&args.drip)
+        .expect("Unknown dividend reinvestment. Possible values: none, fractional, whole");
+    let contributions = args.contribution.map(|amount| Contributions {
+        amount,
+        per_year: Contributions::per_year_from_name(&args.contribution_frequency)
+            .expect("Unknown contribution frequency. Possible values: monthly, quarterly, annual"),
+        growth: args.contribution_growth / 100.0,
+    });
+    let mut forecasts: Vec<(String, Forecast)> = vec![];
+    // Forecast parameters, dividend growth model and dividend history of every company
+    let mut simulated: Vec<(String, ForecastParams, GrowthModel, Vec<(String, f64)>)> = vec![];
+    let mut holdings: Vec<Holding> = vec![];
+    // Number of shares, value and income grow only when dividends are reinvested or
+    // contributions are made
+    let portfolio_caption = |forecast: &Forecast| {
+        let mut caption = match reinvestment {
+            Reinvestment::None => "".to_owned(),
+            _ => format!(
+                " (DRIP Shares: {:.2}, Annual Income[$]: {:.2})",
+                forecast.shares.last().unwrap_or(&0.0),
+                forecast.income.last().unwrap_or(&0.0)
+            ),
+        };
+        if contributions.is_some() {
+            caption += &format!(
+                " (Invested[$]: {:.2}, Shares: {:.2})",
+                forecast.cost_basis.last().unwrap_or(&0.0),
+                forecast.shares.last().unwrap_or(&0.0)
+            );
+        }
+        caption
+    };
+    // Every company gets full capital unless they are forecast as a single portfolio
+    let allocation = args.portfolio.then(|| {
+        Allocation::from_specs(&args.weight, &args.shares).expect("Invalid portfolio allocation")
+    });
+    let num_holdings = companies.len();
+    let holding_capital = |name: &str, share_price: f64| match &allocation {
+        Some(allocation) => allocation
+            .capital_of(name, base_capital, num_holdings, share_price)
+            .expect("Unable to allocate capital of portfolio"),
+        None => base_capital,
+    };
+    // Flat --tax-rate is used unless residence of investor is given
+    let tax_model = args.residence.as_ref().map(|residence| {
+        TaxModel::new(residence, !args.no_w8ben, args.treaty_relief)
+            .expect("Invalid dividend tax model")
+    });
+    let tax_of = |name: &str| {
+        tax_model.as_ref().map(|model| {
+            let domicile = domicile_of(&args.domicile, name);
+            let tax = model
+                .dividend_tax(&domicile)
+                .expect("Unable to tax dividends of company");
+            (domicile, tax)
+        })
+    };
+    // Domicile, tax and net dividends of every company
+    let mut taxes: Vec<(String, String, DividendTax, f64)> = vec![];
+    let tax_caption = |tax: &Option<(String, DividendTax)>, forecast: &Forecast| match tax {
+        None => "".to_owned(),
+        Some((domicile, tax)) => format!(
+            " ({domicile} {tax}, Net Payout[$]: {:.2}, Tax Leakage[$]: {:.2})",
+            forecast.last_payout,
+            tax_leakage(*forecast.gains.last().unwrap_or(&0.0), tax)
+        ),
+    };
+    let growth_model_caption = |growth_model: &GrowthModel| match growth_model {
+        GrowthModel::Constant => "".to_owned(),
+        _ => format!(" ({growth_model})"),
+    };
 
     // make actual plot
     let colors: Vec<&str> = vec!["blue", "green", "navy", "web-green", "#127cc1", "#76B900"];
@@ -278,18 +817,29 @@ fn forecast_dividend_stocks(
 
         match x {
             Target::manual(name,dy,dyg,sp) => {
+                // Dividends of custom company are paid quarterly
+                let num_capitalizations: u32 = 4;
 
                 // Get Dividend prediction
-                let (capital, final_payout, gains) = forecast_dividend_gains(
-                    base_capital,
-                    *dy/100.0,
-                    *dyg/100.0,
-                    *sp,
-                    shares_price_growth_rate,
-                    tax_rate,
-                    &time_data,
+                let invested = holding_capital(name, *sp);
+                let holding_tax = tax_of(name);
+                let params = ForecastParams {
+                    base_capital: invested,
+                    div_yield: *dy / 100.0,
+                    div_growth: *dyg / 100.0,
+                    share_price: *sp,
+                    share_price_growth_rate: shares_price_growth_rate,
+                    tax_rate: holding_tax.as_ref().map_or(tax_rate, |(_, tax)| tax.total()),
                     num_capitalizations,
-                );
+                    reinvestment,
+                    contributions,
+                };
+                let growth_model =
+                    growth_model_of(&args.growth_model, name).expect("Invalid growth model");
+                let annual_rates = growth_model.annual_rates(&params, &[], investment_years);
+                let forecast = forecast_dividends_with_rates(&params, &time_data, &annual_rates);
+                let (capital, final_payout, gains) =
+                    (forecast.final_value, forecast.last_payout, &forecast.gains);
 
                 let caption = match gains.last() {
                     Some(x) => {
@@ -297,11 +847,24 @@ fn forecast_dividend_stocks(
                             max_y = *x;
                         }
                         format!(
-                        "{name}(DIVY[%]: {:.2}, DYG 5G[%]: {:.2}, Price[$]: {:.2}) (Stock[$]: {:.2}, Payout[$]: {:.2},Payout2Investment[%]: {:.2}, Total Dividends Gains[$]: {:.2} )",*dy,*dyg,*sp, capital, final_payout, (final_payout/base_capital)*100.0,x
+                        "{name}(DIVY[%]: {:.2}, DYG 5G[%]: {:.2}, Price[$]: {:.2}) (Stock[$]: {:.2}, Payout[$]: {:.2},Payout2Investment[%]: {:.2}, Total Dividends Gains[$]: {:.2} ){}",*dy,*dyg,*sp, capital, final_payout, (final_payout/invested)*100.0,x, [portfolio_caption(&forecast), growth_model_caption(&growth_model), tax_caption(&holding_tax, &forecast)].concat()
                     )},
                     None => panic!("Error: No dividend data to plot!"),
                 };
-                axes.lines(&time_data, &gains, &[Caption(&caption), Color(colors[i])]);
+                axes.lines(&time_data, gains, &[Caption(&caption), Color(colors[i % colors.len()])]);
+                if let Some(inflation_path) = &inflation_path {
+                    plot_real_terms(axes, name, &time_data, gains, inflation_path, colors[i % colors.len()]);
+                }
+                if let Some((domicile, tax)) = holding_tax {
+                    taxes.push((name.to_string(), domicile, tax, *forecast.gains.last().unwrap_or(&0.0)));
+                }
+                forecasts.push((name.to_string(), forecast));
+                holdings.push(Holding {
+                    name: name.to_string(),
+                    params: params.clone(),
+                    annual_rates,
+                });
+                simulated.push((name.to_string(), params, growth_model, vec![]));
 
             },
             Target::symbol(name) => {
@@ -309,7 +872,7 @@ fn forecast_dividend_stocks(
                 let company = Series::new("", vec![name_str]);
 
 
-                let (share_price, dy, dyg) = match data.clone() {
+                let (share_price, dy, dyg, div_history, num_capitalizations) = match data.clone() {
                     Some(database) => {
 
                         let mut excel: Xlsx<_> = open_workbook(database)
@@ -335,56 +898,134 @@ fn forecast_dividend_stocks(
                             (AnyValue::Float64(valp),AnyValue::Float64(vald),AnyValue::Float64(valg)) => (valp,vald/100.0,valg/100.0),
                             _ => panic!("Unable to get price value"),
                         };
-                        (price, dy, dyg)
+                        // Pay-out frequency is not given in the list, quarterly is assumed
+                        (price, dy, dyg, vec![], 4)
                     }
                     None => {
 
-                        let (share_price, _, divy, frequency,  dgr, _, _, _) =
+                        let polygon_data =
                             investments_forecasting::get_polygon_data(&name).expect("Error: unable to get Data from polygon IO for forecasting");
-                        num_capitalizations = frequency.expect("Cannot forecast dividend gains as there is no dividend data") as u32;
-                        let divy = divy.expect("Cannot forecast dividend gains as there is no dividend data");
-                        let dgr = dgr.expect("Cannot forecast dividend gains as there is no dividend data");
+                        let share_price = polygon_data.share_price;
+                        let frequency = polygon_data.frequency;
+                        let num_capitalizations = (frequency.expect("Cannot forecast dividend gains as there is no dividend data") as u32).clamp(1, 365);
+                        let divy = polygon_data.divy.expect("Cannot forecast dividend gains as there is no dividend data");
+                        let dgr = polygon_data.dgr.expect("Cannot forecast dividend gains as there is no dividend data");
                         log::info!("Forcasting stock: {name} with params: share price({share_price}), Frequency(frequency), Div yield[%]({divy}), DGR5Y[%]({dgr})");
-                        (share_price, divy/100.0, dgr/100.0)
+                        (share_price, divy/100.0, dgr/100.0, polygon_data.div_history, num_capitalizations)
                     },
                 };
 
                 // Get Dividend prediction
-                let (capital, final_payout, gains) = forecast_dividend_gains(
-                    base_capital,
-                    dy,
-                    dyg,
+                let invested = holding_capital(name, share_price);
+                let holding_tax = tax_of(name);
+                let params = ForecastParams {
+                    base_capital: invested,
+                    div_yield: dy,
+                    div_growth: dyg,
                     share_price,
-                    shares_price_growth_rate,
-                    tax_rate,
-                    &time_data,
+                    share_price_growth_rate: shares_price_growth_rate,
+                    tax_rate: holding_tax.as_ref().map_or(tax_rate, |(_, tax)| tax.total()),
                     num_capitalizations,
-                );
+                    reinvestment,
+                    contributions,
+                };
+                let growth_model =
+                    growth_model_of(&args.growth_model, name).expect("Invalid growth model");
+                let annual_rates = growth_model.annual_rates(&params, &div_history, investment_years);
+                let forecast = forecast_dividends_with_rates(&params, &time_data, &annual_rates);
+                let (capital, final_payout, gains) =
+                    (forecast.final_value, forecast.last_payout, &forecast.gains);
                 let caption = match gains.last() {
                     Some(x) => {
                         if *x > max_y {
                             max_y = *x;
                         }
                         format!(
-                        "{name}(DIVY[%]: {:.2}, DYG 5G[%]: {:.2}, Price[$]: {:.2}) (Stock[$]: {:.2}, Payout[$]: {:.2} ,Final DIVY[%]: {:.2}, Total Payout[$]: {:.2} )",dy*100.0,dyg*100.0,share_price, capital, final_payout,num_capitalizations as f64*(final_payout/base_capital)*100.0,x
+                        "{name}(DIVY[%]: {:.2}, DYG 5G[%]: {:.2}, Price[$]: {:.2}) (Stock[$]: {:.2}, Payout[$]: {:.2} ,Final DIVY[%]: {:.2}, Total Payout[$]: {:.2} ){}",dy*100.0,dyg*100.0,share_price, capital, final_payout,num_capitalizations as f64*(final_payout/invested)*100.0,x, [portfolio_caption(&forecast), growth_model_caption(&growth_model), tax_caption(&holding_tax, &forecast)].concat()
                     )},
                     None => panic!("Error: No dividend data to plot!"),
                 };
-                axes.lines(&time_data, &gains, &[Caption(&caption), Color(colors[i])]);
+                axes.lines(&time_data, gains, &[Caption(&caption), Color(colors[i % colors.len()])]);
+                if let Some(inflation_path) = &inflation_path {
+                    plot_real_terms(axes, name, &time_data, gains, inflation_path, colors[i % colors.len()]);
+                }
+                if let Some((domicile, tax)) = holding_tax {
+                    taxes.push((name.to_string(), domicile, tax, *forecast.gains.last().unwrap_or(&0.0)));
+                }
+                forecasts.push((name.to_string(), forecast));
+                holdings.push(Holding {
+                    name: name.to_string(),
+                    params: params.clone(),
+                    annual_rates,
+                });
+                simulated.push((name.to_string(), params, growth_model, div_history));
             },
         }
     });
 
+    let portfolio = allocation.as_ref().map(|_| aggregate(&forecasts));
+    if let Some(portfolio) = &portfolio {
+        let total = *portfolio.gains.last().unwrap_or(&0.0);
+        if total > max_y {
+            max_y = total;
+        }
+        let caption = format!(
+            "Portfolio (Invested[$]: {:.2}, Annual Income[$]: {:.2}, Total Dividends Gains[$]: {total:.2})",
+            portfolio.cost_basis.first().unwrap_or(&0.0),
+            portfolio.income.last().unwrap_or(&0.0)
+        );
+        axes.lines(
+            &time_data,
+            &portfolio.gains,
+            &[Caption(&caption), Color("black")],
+        );
+    }
+
     // Extend Y range to fit plot titles
     axes.set_y_range(
         gnuplot::AutoOption::Fix(0.0),
         gnuplot::AutoOption::Fix(max_y * 1.2 as f64),
     );
 
-    let info = format!(
-        "Notes:\n   * {}% of Tax is applied to every dividend pay-out\n",
-        tax_rate * 100.0
-    );
+    let mut info = match &args.residence {
+        Some(residence) => {
+            let leakage: f64 = taxes
+                .iter()
+                .map(|(_, _, tax, net)| tax_leakage(*net, tax))
+                .sum();
+            println!("Dividend taxes of {residence} resident:");
+            taxes.iter().for_each(|(name, domicile, tax, net)| {
+                println!(
+                    "   {name} ({domicile}): {tax}, Net Dividends[$]: {net:.2}, Tax[$]: {:.2}, Tax Leakage[$]: {:.2}",
+                    tax_paid(*net, tax.total()),
+                    tax_leakage(*net, tax)
+                )
+            });
+            println!("   Total Tax Leakage[$]: {leakage:.2}");
+            format!(
+                "Notes:\n   * Dividends are taxed by withholding tax of domicile and top-up tax of {residence} (Total Tax Leakage[$]: {leakage:.2})\n"
+            )
+        }
+        None => format!(
+            "Notes:\n   * {}% of Tax is applied to every dividend pay-out\n",
+            tax_rate * 100.0
+        ),
+    };
+    if let Some(c) = &contributions {
+        info += &format!(
+            "   * {:.2}$ invested {} times a year at projected share price (growing {:.2}% a year)\n",
+            c.amount,
+            c.per_year,
+            c.growth * 100.0
+        );
+    }
+    if let Some(inflation_path) = &inflation_path {
+        let rates: Vec<String> = inflation_path.iter().map(|r| format!("{r:.2}")).collect();
+        info += &format!(
+            "   * Dashed lines are in real terms (inflation of consecutive years[%]: {})\n",
+            rates.join(", ")
+        );
+    }
     axes.label(
         &info,
         Coordinate::Graph(0.02),
@@ -393,52 +1034,176 @@ fn forecast_dividend_stocks(
     );
 
     fg.show().expect("Error plotting");
-}
-
-fn forecast_dividend_gains(
-    base_capital: f64,
-    div_yield: f64,
-    div_yield_growth_5y: f64,
-    share_price: f64,
-    share_price_growth_rate: f64,
-    tax_rate: f64,
-    time_line: &Vec<u32>,
-    num_capitalizations: u32,
-) -> (f64, f64, Vec<f64>) {
-    let mut gains: Vec<f64> = vec![];
-
-    let mut curr_gain: f64 = 0.0;
-    let mut share_price = share_price;
-    let mut curr_div = div_yield * share_price;
 
-    let mut last_gain = 0.0;
-
-    let capitalization_period = 365 / num_capitalizations;
+    if reinvestment != Reinvestment::None || contributions.is_some() || portfolio.is_some() {
+        plot_portfolio(
+            &suffixed_output_name(output_file_name, "portfolio"),
+            &time_data,
+            &forecasts,
+            portfolio.as_ref(),
+        );
+    }
 
-    let num_shares = base_capital / share_price;
-    log::info!("Company: Price[$]: {share_price},  Num Shares: {num_shares} , ANNUAL DIV PER SHARE[$]: {curr_div}");
+    if !args.strategy.is_empty() {
+        if portfolio.is_none() {
+            panic!("\nError: Dividend strategies can be compared only for --portfolio");
+        }
+        let strategies: Vec<Strategy> = args
+            .strategy
+            .iter()
+            .map(|name| {
+                Strategy::from_name(name).expect(
+                    "Unknown strategy. Possible values: payer, highest-yield, underweight, rebalance[:<years>], cash",
+                )
+            })
+            .collect();
+        compare_strategies(
+            &suffixed_output_name(output_file_name, "strategies"),
+            &time_data,
+            &holdings,
+            &strategies,
+        );
+    }
 
-    time_line.iter().for_each(|x| {
-        if x % capitalization_period == 0 {
-            let g: f64;
-            g = compute_dividend_gain(num_shares, curr_div, num_capitalizations, tax_rate);
-            curr_gain += g;
-            last_gain = g;
-            log::info!(
-                "Company: Price[$]: {share_price},  Num Shares: {num_shares} ,PAYED DIV[$]: {g}"
+    if let Some(num_paths) = args.monte_carlo {
+        let prices = args
+            .prices
+            .as_ref()
+            .map(|path| PriceHistory::from_csv(path).expect("Unable to load prices"));
+        simulated.iter().for_each(|(name, params, growth_model, div_history)| {
+            let estimated_dgr = Distribution::dividend_growth(div_history);
+            let estimated_price_growth = prices
+                .as_ref()
+                .and_then(|prices| Distribution::price_growth(prices, name));
+            if let Some(estimated) = estimated_price_growth {
+                println!(
+                    "{name}: share price growth mean[%] and standard deviation[%] estimated from price history: {:.2}, {:.2}",
+                    estimated.mean * 100.0,
+                    estimated.std_dev * 100.0
+                );
+            } else if prices.is_some() {
+                println!("{name}: not enough price history to estimate share price growth");
+            }
+            let price_growth = Distribution {
+                mean: estimated_price_growth.map_or(params.share_price_growth_rate, |e| e.mean),
+                std_dev: match (args.price_volatility, estimated_price_growth) {
+                    (Some(volatility), _) => volatility / 100.0,
+                    (None, Some(estimated)) => estimated.std_dev,
+                    (None, None) => DEFAULT_PRICE_VOLATILITY / 100.0,
+                },
+            };
+            let dgr_volatility = match (args.dgr_volatility, estimated_dgr) {
+                (Some(volatility), _) => volatility / 100.0,
+                (None, Some(estimated)) => {
+                    println!(
+                        "{name}: DGR standard deviation[%] estimated from dividend history: {:.2}",
+                        estimated.std_dev * 100.0
+                    );
+                    estimated.std_dev
+                }
+                (None, None) => DEFAULT_DGR_VOLATILITY / 100.0,
+            };
+            // Years are centered on rates of growth model, share price grows at estimated rate
+            let annual_means = growth_model.annual_rates(
+                &ForecastParams {
+                    share_price_growth_rate: price_growth.mean,
+                    ..params.clone()
+                },
+                div_history,
+                investment_years,
             );
+            let monte_carlo = MonteCarloParams {
+                price_growth,
+                div_growth: Distribution {
+                    mean: params.div_growth,
+                    std_dev: dgr_volatility,
+                },
+                annual_means,
+                correlation: args.correlation,
+                num_paths,
+                seed: args.seed,
+            };
+            let simulation = montecarlo::simulate(params, &time_data, &monte_carlo);
+            if let Some(target_income) = args.target_income {
+                println!(
+                    "{name}: probability of Annual Income[$] >= {target_income:.2}: {:.1}%",
+                    simulation.probability_of_income(target_income) * 100.0
+                );
+            }
+            plot_monte_carlo(
+                &suffixed_output_name(output_file_name, &format!("montecarlo-{name}")),
+                name,
+                &simulation,
+                &monte_carlo,
+                args.target_income,
+            );
+        });
+    }
+}
+
+/// Print errors of forecasts made at past date for companies given with --company
+fn backtest_companies(args: &Args, as_of: &str) {
+    let as_of = NaiveDate::parse_from_str(as_of, "%Y-%m-%d")
+        .expect("Backtest date should be given as YYYY-MM-DD");
+    let path = args
+        .prices
+        .as_ref()
+        .expect("Backtest requires --prices with closing prices of companies");
+    let prices = PriceHistory::from_csv(path).expect("Unable to load prices");
+    let today = Utc::now().date_naive();
+
+    let mut backtests = vec![];
+    args.company.iter().for_each(|symbol| {
+        let growth_model =
+            growth_model_of(&args.growth_model, symbol).expect("Invalid growth model");
+        let backtest = investments_forecasting::get_polygon_data(symbol).and_then(|data| {
+            backtest::backtest(
+                symbol,
+                &data.div_history,
+                &prices,
+                as_of,
+                today,
+                args.years,
+                args.share_price_growth_rate / 100.0,
+                &growth_model,
+            )
+        });
+        match backtest {
+            Ok(backtest) => {
+                println!(
+                    "{symbol}: forecast as of {as_of} (Trailing Div[$]: {:.2}, DGR 5Y[%]: {:.2}, {growth_model})",
+                    backtest.trailing_div, backtest.dgr
+                );
+                let optional = |v: Option<f64>| v.map_or("-".to_owned(), |v| format!("{v:.2}"));
+                backtest.errors.iter().for_each(|e| {
+                    println!(
+                        "   Year {}: Div[$] projected {:.2}, actual {:.2}, error[%] {} | Price[$] projected {:.2}, actual {:.2}, error[%] {}",
+                        e.year,
+                        e.projected_div,
+                        e.actual_div,
+                        optional(e.div_error),
+                        e.projected_price,
+                        e.actual_price,
+                        optional(e.price_error)
+                    )
+                });
+                backtests.push(backtest);
+            }
+            Err(e) => println!("{symbol}: unable to backtest: {e}"),
         }
-        if x % 365 == 0 {
-            // Share price and div yeild update
-            // Compute new share price
-            share_price = share_price * (1.0 + share_price_growth_rate);
-            // Compute new Div Yield
-            curr_div = curr_div * (1.0 + div_yield_growth_5y);
-        }
-        gains.push(curr_gain);
     });
 
-    (num_shares * share_price, last_gain, gains)
+    println!("Mean absolute error by horizon:");
+    backtest::mean_absolute_errors(&backtests)
+        .iter()
+        .for_each(|(year, div_error, price_error)| {
+            let optional = |v: &Option<f64>| v.map_or("-".to_owned(), |v| format!("{v:.2}"));
+            println!(
+                "   Year {year}: Div error[%] {}, Price error[%] {}",
+                optional(div_error),
+                optional(price_error)
+            )
+        });
 }
 
 fn main() {
@@ -447,43 +1212,39 @@ fn main() {
     investments_forecasting::init_logging_infrastructure();
     let args = Args::parse();
 
+    if let Some(as_of) = &args.backtest {
+        return backtest_companies(&args, as_of);
+    }
+
     forecast_low_risk_instruments(args.capital);
 
+    let inflation_path = args.cpi.as_ref().map(|path| {
+        let cpi = CpiSeries::from_csv(path).expect("Unable to load CPI data");
+        if let Some((as_of, inflation)) = cpi.trailing_inflation() {
+            println!("Trailing inflation: {inflation:.2}% (as of {as_of})");
+        }
+        cpi.inflation_path(Utc::now().year(), args.years)
+    });
+
     let mut targets: Vec<Target> = vec![];
     args.company
         .iter()
         .for_each(|symbol| targets.push(Target::symbol(&symbol)));
 
-    if let Some(name) = args.custom_name {
+    if let Some(name) = &args.custom_name {
         match (
             args.custom_div_yield,
             args.custom_div_growth,
             args.custom_price,
         ) {
             (Some(dy), Some(dg), Some(p)) => {
-                targets.push(Target::manual(&name, dy, dg, p));
-                forecast_dividend_stocks(
-                    args.output.as_ref(),
-                    args.capital,
-                    args.data,
-                    targets,
-                    args.years,
-                    args.share_price_growth_rate,
-                    args.tax_rate,
-                );
+                targets.push(Target::manual(name, dy, dg, p));
+                forecast_dividend_stocks(&args, targets, inflation_path);
             }
             _ => panic!("\nError: Missing some custom arguments"),
         }
     } else {
-        forecast_dividend_stocks(
-            args.output.as_ref(),
-            args.capital,
-            args.data,
-            targets,
-            args.years,
-            args.share_price_growth_rate,
-            args.tax_rate,
-        );
+        forecast_dividend_stocks(&args, targets, inflation_path);
     }
 }
 
@@ -508,168 +1269,14 @@ mod tests {
     }
 
     #[test]
-    fn test_compute_dividend_gains() -> Result<(), String> {
-        let num_shares = 10.0;
-        let div_yield_rate = 0.1;
-        let share_price = 100.0;
-        let num_capitalizations = 4;
-        let transaction_tax = 0.1;
-
-        // num_shares*share_price*div_yield_rate / (num_capitalizations as f64) * (1.0 - transaction_tax);
-        //1000.0*0.1/(4.0)*0.9 = 25.0*0.9 = 22.5;
-        let ref_gain = 22.5;
-        let curr_div = share_price * div_yield_rate;
-
-        assert_eq!(
-            ref_gain,
-            compute_dividend_gain(num_shares, curr_div, num_capitalizations, transaction_tax)
-        );
-
+    fn test_specs_of_company() -> Result<(), String> {
+        let specs = vec!["constant".to_owned(), "abm=regression".to_owned()];
+        assert_eq!(growth_model_of(&specs, "ABM")?, GrowthModel::Regression);
+        assert_eq!(growth_model_of(&specs, "XOM")?, GrowthModel::Constant);
+
+        let specs = vec!["Ctbi = PL".to_owned()];
+        assert_eq!(domicile_of(&specs, "CTBI"), "PL");
+        assert_eq!(domicile_of(&specs, "ABM"), "US");
         Ok(())
     }
-
-    #[test]
-    fn test_dividend_gains() -> Result<(), String> {
-        let time_data: Vec<u32> = (1u32..366).collect();
-
-        let base_capital = 1000.0;
-        let tax_rate = 0.15;
-        let num_capitalizations: u32 = 1;
-        let div_yield: f64 = 0.5;
-        let div_yield_growth_5y: f64 = 0.10;
-        let share_price: f64 = 100.0;
-        let share_price_growth_rate: f64 = 0.1;
-
-        // final capital : (1000.0 * (1.0 + 0.1)) = 1100.0
-        let ref_final_capital = 1100.00;
-
-        // total dividend payout : 1000.0*(0.5)*(1.0-0.15)
-        let ref_total_payout: f64 = 425.0;
-
-        // final payout : 1000.0*(0.5)/1.0*(1.0-0.15)
-        let ref_final_payout: f64 = 425.0;
-
-        // Compute dividend gains and value of stock
-        let (final_capital, final_payout, gains) = forecast_dividend_gains(
-            base_capital,
-            div_yield,
-            div_yield_growth_5y,
-            share_price,
-            share_price_growth_rate,
-            tax_rate,
-            &time_data,
-            num_capitalizations,
-        );
-
-        assert_eq!(ref_final_capital, ((final_capital * 100.0).round() / 100.0));
-        assert_eq!(ref_final_payout, ((final_payout * 100.0).round() / 100.0));
-        match gains.last() {
-            Some(total_payout) => {
-                assert_eq!(*total_payout, ref_total_payout);
-                return Ok(());
-            }
-            None => return Err(format!("Error: No dividend gains found!")),
-        }
-    }
-
-    #[test]
-    fn test_dividend_gains_2() -> Result<(), String> {
-        let time_data: Vec<u32> = (1u32..366).collect();
-
-        let base_capital = 1000.0;
-        let tax_rate = 0.15;
-        let num_capitalizations: u32 = 4;
-        let div_yield: f64 = 0.5;
-        let div_yield_growth_5y: f64 = 0.10;
-        let share_price: f64 = 100.0;
-        let share_price_growth_rate: f64 = 0.1;
-
-        // final capital : (1000.0 * (1.0 + 0.1/4.0) )*(1.025)*(1.025)*(1.025) = 1103.812891
-        let ref_final_capital = 1100.00;
-
-        // final payout : 1000.0*(0.5)/4.0*(1.0-0.15)
-
-        // total dividend payout :
-        // 1000.0*0.5/4.0*(1.0-0.15) = 106.25 <- c1
-        // 1000.0*(0.5/4.0)*(1.0-0.15)= 106.25 <- c2
-        // 1000.0*0.5/4.0*(1.0-0.15) = 106.25 <- c3
-        // (1000.0*(0.5/4.0)*(1.0-0.15)= 106.25 <- c4 (final payout)
-        // c1 + c2 + c3 + c4 = 106.25*4.0 = 425.0
-        let ref_final_payout: f64 = 106.25;
-        let ref_total_payout: f64 = 425.0;
-
-        // Compute dividend gains and value of stock
-        let (final_capital, final_payout, gains) = forecast_dividend_gains(
-            base_capital,
-            div_yield,
-            div_yield_growth_5y,
-            share_price,
-            share_price_growth_rate,
-            tax_rate,
-            &time_data,
-            num_capitalizations,
-        );
-
-        assert_eq!(ref_final_payout, ((final_payout * 100.0).round() / 100.0));
-        assert_eq!(ref_final_capital, ((final_capital * 100.0).round() / 100.0));
-        match gains.last() {
-            Some(total_payout) => {
-                assert_eq!(((*total_payout * 100.0).round() / 100.0), ref_total_payout);
-                return Ok(());
-            }
-            None => return Err(format!("Error: No dividend gains found!")),
-        }
-    }
-
-    #[test]
-    fn test_dividend_gains_3() -> Result<(), String> {
-        let num_years = 2;
-        let time_data: Vec<u32> = (1u32..365 * num_years + 1).collect();
-
-        let base_capital = 1000.0;
-        let tax_rate = 0.15;
-        let num_capitalizations: u32 = 4;
-        let div_yield: f64 = 0.5;
-        let div_yield_growth_5y: f64 = 0.10;
-        let share_price: f64 = 100.0;
-        let share_price_growth_rate: f64 = 0.1;
-
-        // final capital : (1000.0 * (1.0 + 0.1) )*(1.0+ 0.1) =1210.0
-        let ref_final_capital = 1210.00;
-
-        // total dividend payout :
-        // 1000.0*0.5/4.0*(1.0-0.15) = 106.25 <- c1
-        // (1000.0*(0.5/4.0)*(1.0-0.15)= 106.25 <- c2
-        // 1000.0*0.5/4.0*(1.0-0.15) = 106.25 <- c3
-        // (1000.0*(0.5/4.0)*(1.0-0.15)= 106.25 <- c4
-        // ((1000.0*0.5)*(1.0+0.1))/4.0*(1.0-0.15) = 116.875 <- c5
-        // ((1000.0*0.5)*(1.0+0.1))/4.0*(1.0-0.15) = 116.875 <- c6
-        // ((1000.0*0.5)*(1.0+0.1))/4.0*(1.0-0.15) = 116.875 <- c7
-        // ((1000.0*0.5)*(1.0+0.1))/4.0*(1.0-0.15) = 116.875 <- c8 (final payout)
-        // c1 + c2 + c3 + c4 + c5 +c6 +c7 +c8 = 106.25*4.0 + 116.875*4.0 = 892.5
-        let ref_total_payout: f64 = 892.5;
-        let ref_final_payout: f64 = 116.88;
-
-        // Compute dividend gains and value of stock
-        let (final_capital, final_payout, gains) = forecast_dividend_gains(
-            base_capital,
-            div_yield,
-            div_yield_growth_5y,
-            share_price,
-            share_price_growth_rate,
-            tax_rate,
-            &time_data,
-            num_capitalizations,
-        );
-
-        assert_eq!(ref_final_payout, ((final_payout * 100.0).round() / 100.0));
-        assert_eq!(ref_final_capital, ((final_capital * 100.0).round() / 100.0));
-        match gains.last() {
-            Some(total_payout) => {
-                assert_eq!(((*total_payout * 100.0).round() / 100.0), ref_total_payout);
-                return Ok(());
-            }
-            None => return Err(format!("Error: No dividend gains found!")),
-        }
-    }
 }
@@ -0,0 +1,218 @@
+use polars::prelude::*;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use std::fs::File;
+use std::io::Write;
+
+/// Table of companies as it was after given screening stage
+pub type Stage = (String, DataFrame);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Companies of final stage
+    Csv,
+    /// Companies of final stage, one JSON object per line
+    JsonLines,
+    /// Table per stage
+    Markdown,
+    /// Workbook with sheet per stage
+    Xlsx,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name.trim().to_lowercase().as_str() {
+            "csv" => Some(OutputFormat::Csv),
+            "json" | "jsonl" | "json-lines" => Some(OutputFormat::JsonLines),
+            "md" | "markdown" => Some(OutputFormat::Markdown),
+            "xlsx" => Some(OutputFormat::Xlsx),
+            _ => None,
+        }
+    }
+}
+
+/// Write screening stages to a file in given format
+pub fn write_stages(
+    stages: &[Stage],
+    format: OutputFormat,
+    path: &str,
+) -> Result<(), &'static str> {
+    let (_, last) = stages.last().ok_or("Error: Nothing to write")?;
+    match format {
+        OutputFormat::Csv => {
+            let mut file = create(path)?;
+            CsvWriter::new(&mut file)
+                .finish(&mut last.clone())
+                .map_err(|e| {
+                    log::error!("Unable to write {path}: {e}");
+                    "Error: Unable to write CSV"
+                })
+        }
+        OutputFormat::JsonLines => {
+            let mut file = create(path)?;
+            JsonWriter::new(&mut file)
+                .with_json_format(JsonFormat::JsonLines)
+                .finish(&mut last.clone())
+                .map_err(|e| {
+                    log::error!("Unable to write {path}: {e}");
+                    "Error: Unable to write JSON lines"
+                })
+        }
+        OutputFormat::Markdown => {
+            let mut file = create(path)?;
+            file.write_all(markdown(stages).as_bytes()).map_err(|e| {
+                log::error!("Unable to write {path}: {e}");
+                "Error: Unable to write Markdown"
+            })
+        }
+        OutputFormat::Xlsx => write_xlsx(stages, path).map_err(|e| {
+            log::error!("Unable to write {path}: {e}");
+            "Error: Unable to write XLSX"
+        }),
+    }?;
+    log::info!("Results written to {path}");
+    Ok(())
+}
+
+fn create(path: &str) -> Result<File, &'static str> {
+    File::create(path).map_err(|e| {
+        log::error!("Unable to create {path}: {e}");
+        "Error: Unable to create output file"
+    })
+}
+
+/// Text of a table cell as presented in reports (numbers with 2 decimal places)
+pub fn cell_text(value: &AnyValue) -> String {
+    match value {
+        AnyValue::Null => "".to_owned(),
+        AnyValue::Float64(v) => format!("{v:.2}"),
+        AnyValue::Float32(v) => format!("{v:.2}"),
+        AnyValue::Utf8(v) => v.to_string(),
+        v => v.to_string(),
+    }
+}
+
+/// Markdown document with a table per stage
+pub fn markdown(stages: &[Stage]) -> String {
+    stages
+        .iter()
+        .map(|(name, df)| {
+            let header = df.get_column_names().join(" | ");
+            let separator = vec!["---"; df.width()].join(" | ");
+            let rows: Vec<String> = (0..df.height())
+                .map(|i| {
+                    let cells: Vec<String> = df
+                        .get_columns()
+                        .iter()
+                        .map(|c| {
+                            c.get(i)
+                                .map(|v| cell_text(&v).replace('|', "\\|"))
+                                .unwrap_or_default()
+                        })
+                        .collect();
+                    format!("| {} |", cells.join(" | "))
+                })
+                .collect();
+            format!(
+                "## {name}\n\n| {header} |\n| {separator} |\n{}\n",
+                rows.join("\n")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn write_xlsx(stages: &[Stage], path: &str) -> Result<(), XlsxError> {
+    let mut workbook = Workbook::new();
+    let header_format = Format::new().set_bold();
+    let number_format = Format::new().set_num_format("0.00");
+
+    stages.iter().try_for_each(|(name, df)| {
+        let worksheet = workbook.add_worksheet();
+        // Sheet names are limited to 31 characters and some characters are forbidden
+        let sheet_name: String = name
+            .chars()
+            .map(|c| if "[]:*?/\\".contains(c) { '-' } else { c })
+            .take(31)
+            .collect();
+        worksheet.set_name(sheet_name)?;
+
+        df.get_columns()
+            .iter()
+            .enumerate()
+            .try_for_each(|(col, series)| {
+                let col = col as u16;
+                worksheet.write_string_with_format(0, col, series.name(), &header_format)?;
+                (0..series.len()).try_for_each(|i| {
+                    let row = i as u32 + 1;
+                    match series.get(i) {
+                        Ok(AnyValue::Null) | Err(_) => Ok(()),
+                        Ok(AnyValue::Boolean(v)) => {
+                            worksheet.write_boolean(row, col, v).map(|_| ())
+                        }
+                        Ok(AnyValue::Utf8(v)) => worksheet.write_string(row, col, v).map(|_| ()),
+                        Ok(AnyValue::Float64(v)) => worksheet
+                            .write_number_with_format(row, col, v, &number_format)
+                            .map(|_| ()),
+                        Ok(v) => match v.extract::<f64>() {
+                            Some(number) => worksheet.write_number(row, col, number).map(|_| ()),
+                            None => worksheet.write_string(row, col, v.to_string()).map(|_| ()),
+                        },
+                    }
+                })
+            })?;
+        worksheet.set_freeze_panes(1, 0)?;
+        worksheet.autofit();
+        Ok::<(), XlsxError>(())
+    })?;
+    workbook.save(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_format_from_name() -> Result<(), String> {
+        assert_eq!(OutputFormat::from_name("CSV"), Some(OutputFormat::Csv));
+        assert_eq!(
+            OutputFormat::from_name("jsonl"),
+            Some(OutputFormat::JsonLines)
+        );
+        assert_eq!(OutputFormat::from_name("pdf"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_markdown() -> Result<(), String> {
+        let s1 = Series::new("Symbol", &["ABM", "INTC"]);
+        let s2 = Series::new("Div Yield", &[Some(5.544), None]);
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let stages = vec![("Div Yield".to_owned(), df)];
+        assert_eq!(
+            markdown(&stages),
+            "## Div Yield\n\n| Symbol | Div Yield |\n| --- | --- |\n| ABM | 5.54 |\n| INTC |  |\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_stages() -> Result<(), String> {
+        let s1 = Series::new("Symbol", &["ABM", "INTC"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32]);
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+        let stages = vec![
+            ("All".to_owned(), df.clone()),
+            ("Div Yield".to_owned(), df.head(Some(1))),
+        ];
+
+        let path = crate::TempPath::new("export.csv");
+        let path = path.as_str();
+        write_stages(&stages, OutputFormat::Csv, path)?;
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        assert_eq!(content, "Symbol,Div Yield\nABM,5.54\n");
+
+        assert!(write_stages(&[], OutputFormat::Csv, path).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use crate::FinancialReport;
+
+/// Growth of a single figure over consecutive financial reports
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GrowthTrend {
+    /// Compound annual growth rate[%] between oldest and newest annual report
+    pub cagr: Option<f64>,
+    /// Slope of linear trend fitted into annual values relative to their mean[% per year]
+    pub trend: Option<f64>,
+    /// Growth[%] of most recent quarter compared to the same quarter a year before
+    pub latest_quarter_yoy: Option<f64>,
+    /// Number of annual reports the figure was present in
+    pub num_years: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GrowthTrends {
+    pub revenue: GrowthTrend,
+    pub eps: GrowthTrend,
+    pub operating_cash_flow: GrowthTrend,
+    pub fcf_per_share: GrowthTrend,
+}
+
+/// Share count change above this annual rate[%] of a dividend payer means dividends are
+/// funded by issuing new shares
+const DILUTION_THRESHOLD: f64 = 2.0;
+
+/// Share count trend and total cash returned to shareholders
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShareholderYield {
+    /// Annual rate of share count change[%] over available annual reports (negative means buybacks)
+    pub share_count_cagr: Option<f64>,
+    /// Share count reduction[%] in most recent fiscal year (negative means dilution)
+    pub net_buyback_yield: Option<f64>,
+    /// Dividend yield plus net buyback yield[%]
+    pub shareholder_yield: Option<f64>,
+    /// Dividend payer whose share count is growing faster than DILUTION_THRESHOLD
+    pub is_dilutive_payer: bool,
+}
+
+/// Slope of least squares line fitted into (x, y) points
+pub fn linear_slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (num, den) = points.iter().fold((0.0, 0.0), |(num, den), (x, y)| {
+        let dx = x - mean_x;
+        (num + dx * (y - mean_y), den + dx * dx)
+    });
+    Some(num / den)
+}
+
+/// Slope of least squares line fitted into consecutive samples (change per sample)
+pub fn trend(samples: &[f64]) -> Option<f64> {
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .enumerate()
+        .map(|(x, y)| (x as f64, *y))
+        .collect();
+    linear_slope(&points)
+}
+
+/// (fiscal year, value) of annual reports the figure is present in. Reports of unknown
+/// fiscal year are skipped
+fn yearly_values(
+    annual_reports: &[FinancialReport],
+    figure: fn(&FinancialReport) -> Option<f64>,
+) -> Vec<(i32, f64)> {
+    annual_reports
+        .iter()
+        .filter_map(|r| Some((r.fiscal_year.parse::<i32>().ok()?, figure(r)?)))
+        .collect()
+}
+
+/// Compound annual growth rate[%] of (fiscal year, value) ordered from older to newer.
+/// Growth is spread over the actual number of years between first and last value, so missing
+/// years still count. Non-positive first or last value makes CAGR meaningless
+pub fn cagr(values: &[(i32, f64)]) -> Option<f64> {
+    let (first_year, first) = *values.first()?;
+    let (last_year, last) = *values.last()?;
+    if last_year <= first_year || first <= 0.0 || last <= 0.0 {
+        return None;
+    }
+    let num_years = (last_year - first_year) as f64;
+    Some(((last / first).powf(1.0 / num_years) - 1.0) * 100.0)
+}
+
+/// Linear trend of (fiscal year, value) relative to their mean magnitude[% per year]
+fn relative_trend(values: &[(i32, f64)]) -> Option<f64> {
+    let points: Vec<(f64, f64)> = values.iter().map(|(x, y)| (*x as f64, *y)).collect();
+    let mean = points.iter().map(|(_, y)| y.abs()).sum::<f64>() / points.len() as f64;
+    if mean == 0.0 {
+        return None;
+    }
+    linear_slope(&points).map(|slope| slope / mean * 100.0)
+}
+
+/// Growth[%] of most recent quarter over the same fiscal period of preceding year
+fn latest_quarter_yoy(
+    quarterly_reports: &[FinancialReport],
+    figure: fn(&FinancialReport) -> Option<f64>,
+) -> Option<f64> {
+    let latest = quarterly_reports
+        .iter()
+        .rev()
+        .find(|r| figure(r).is_some())?;
+    let previous_year = (latest.fiscal_year.parse::<i32>().ok()? - 1).to_string();
+    let previous = quarterly_reports
+        .iter()
+        .find(|r| r.fiscal_year == previous_year && r.fiscal_period == latest.fiscal_period)?;
+    match (figure(latest), figure(previous)) {
+        (Some(latest), Some(previous)) if previous > 0.0 => Some((latest / previous - 1.0) * 100.0),
+        _ => None,
+    }
+}
+
+fn calculate_growth_trend(
+    annual_reports: &[FinancialReport],
+    quarterly_reports: &[FinancialReport],
+    figure: fn(&FinancialReport) -> Option<f64>,
+) -> GrowthTrend {
+    let values = yearly_values(annual_reports, figure);
+    GrowthTrend {
+        cagr: cagr(&values),
+        trend: relative_trend(&values),
+        latest_quarter_yoy: latest_quarter_yoy(quarterly_reports, figure),
+        num_years: values.len(),
+    }
+}
+
+/// Growth of revenue, EPS, operating cash flow and free cash flow per share based on
+/// annual and quarterly reports ordered from older to newer
+pub fn calculate_growth_trends(
+    annual_reports: &[FinancialReport],
+    quarterly_reports: &[FinancialReport],
+) -> GrowthTrends {
+    let growth = GrowthTrends {
+        revenue: calculate_growth_trend(annual_reports, quarterly_reports, |r| r.revenues),
+        eps: calculate_growth_trend(annual_reports, quarterly_reports, |r| r.eps()),
+        operating_cash_flow: calculate_growth_trend(annual_reports, quarterly_reports, |r| {
+            r.operating_cash_flow
+        }),
+        fcf_per_share: calculate_growth_trend(annual_reports, quarterly_reports, |r| {
+            r.fcf_per_share()
+        }),
+    };
+    log::info!("Growth trends: {growth:?}");
+    growth
+}
+
+/// Share count trend of annual reports ordered from older to newer and shareholder yield
+/// based on Dividend Yield[%]
+pub fn calculate_shareholder_yield(
+    annual_reports: &[FinancialReport],
+    div_yield: Option<f64>,
+) -> ShareholderYield {
+    let share_counts: Vec<Option<f64>> = annual_reports
+        .iter()
+        .map(|r| r.basic_average_shares)
+        .collect();
+    let share_count_cagr = cagr(&yearly_values(annual_reports, |r| r.basic_average_shares));
+
+    let mut recent = share_counts.iter().rev().map_while(|x| *x);
+    let net_buyback_yield = match (recent.next(), recent.next()) {
+        (Some(last), Some(previous)) if previous > 0.0 => {
+            Some((previous - last) / previous * 100.0)
+        }
+        _ => None,
+    };
+    let shareholder_yield = match (div_yield, net_buyback_yield) {
+        (Some(divy), Some(buyback)) => Some(divy + buyback),
+        _ => None,
+    };
+    let is_dilutive_payer = div_yield.map_or(false, |divy| divy > 0.0)
+        && share_count_cagr.map_or(false, |rate| rate > DILUTION_THRESHOLD);
+
+    let shareholder_yield = ShareholderYield {
+        share_count_cagr,
+        net_buyback_yield,
+        shareholder_yield,
+        is_dilutive_payer,
+    };
+    log::info!("Shareholder yield: {shareholder_yield:?}");
+    shareholder_yield
+}
+
+/// Dividend growth is backed by business when it does not outpace growth of earnings or
+/// free cash flow per share (whichever is higher). Otherwise dividend grows by payout
+/// expansion. None when there is no data on business growth
+pub fn is_dividend_growth_backed(dgr: f64, growth: &GrowthTrends) -> Option<bool> {
+    let business_growth = match (growth.eps.cagr, growth.fcf_per_share.cagr) {
+        (Some(eps), Some(fcf)) => eps.max(fcf),
+        (Some(eps), None) => eps,
+        (None, Some(fcf)) => fcf,
+        (None, None) => return None,
+    };
+    Some(dgr <= business_growth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round2(val: f64) -> f64 {
+        (val * 100.0).round() / 100.0
+    }
+
+    fn report(fiscal_year: &str, fiscal_period: &str, revenues: Option<f64>) -> FinancialReport {
+        FinancialReport {
+            fiscal_year: fiscal_year.to_owned(),
+            fiscal_period: fiscal_period.to_owned(),
+            revenues,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_trend() -> Result<(), String> {
+        assert_eq!(trend(&[50.0]), None);
+        assert_eq!(trend(&[50.0, 60.0, 70.0]), Some(10.0));
+        assert_eq!(trend(&[70.0, 70.0]), Some(0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cagr() -> Result<(), String> {
+        assert_eq!(cagr(&[(2021, 100.0)]), None);
+        assert_eq!(
+            cagr(&[(2021, 100.0), (2022, 121.0)]).map(round2),
+            Some(21.0)
+        );
+        // Missing year in between still counts as a year of growth
+        assert_eq!(
+            cagr(&[(2021, 100.0), (2023, 121.0)]).map(round2),
+            Some(10.0)
+        );
+        assert_eq!(cagr(&[(2021, -10.0), (2022, 121.0)]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_trend() -> Result<(), String> {
+        // Slope: 10 per year, mean: 110
+        assert_eq!(
+            relative_trend(&[(2021, 100.0), (2022, 110.0), (2023, 120.0)]).map(round2),
+            Some(9.09)
+        );
+        // Slope: 10 per year over a gap of two years
+        assert_eq!(
+            relative_trend(&[(2021, 100.0), (2023, 120.0)]).map(round2),
+            Some(9.09)
+        );
+        assert_eq!(relative_trend(&[(2022, 110.0)]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_calculate_growth_trends() -> Result<(), String> {
+        let annual = vec![
+            report("2021", "FY", Some(100.0)),
+            report("2022", "FY", None),
+            report("2023", "FY", Some(121.0)),
+        ];
+        let quarterly = vec![
+            report("2022", "Q2", Some(25.0)),
+            report("2022", "Q3", Some(30.0)),
+            report("2023", "Q2", Some(30.0)),
+            report("2023", "Q3", Some(33.0)),
+        ];
+        let growth = calculate_growth_trends(&annual, &quarterly);
+        assert_eq!(growth.revenue.cagr.map(round2), Some(10.0));
+        assert_eq!(growth.revenue.latest_quarter_yoy.map(round2), Some(10.0));
+        assert_eq!(growth.revenue.num_years, 2);
+        assert_eq!(growth.eps, GrowthTrend::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_calculate_shareholder_yield() -> Result<(), String> {
+        let with_shares = |shares: &[Option<f64>]| -> Vec<FinancialReport> {
+            shares
+                .iter()
+                .zip(2021..)
+                .map(|(s, year)| FinancialReport {
+                    fiscal_year: year.to_string(),
+                    basic_average_shares: *s,
+                    ..Default::default()
+                })
+                .collect()
+        };
+
+        // Buybacks: 1000 -> 980 -> 950
+        let reports = with_shares(&[Some(1000.0), Some(980.0), Some(950.0)]);
+        let result = calculate_shareholder_yield(&reports, Some(3.0));
+        assert_eq!(result.share_count_cagr.map(round2), Some(-2.53));
+        assert_eq!(result.net_buyback_yield.map(round2), Some(3.06));
+        assert_eq!(result.shareholder_yield.map(round2), Some(6.06));
+        assert!(!result.is_dilutive_payer);
+
+        // Dilution: 1000 -> 1100 -> 1210 while paying dividends
+        let reports = with_shares(&[Some(1000.0), Some(1100.0), Some(1210.0)]);
+        let result = calculate_shareholder_yield(&reports, Some(8.0));
+        assert_eq!(result.share_count_cagr.map(round2), Some(10.0));
+        assert_eq!(result.net_buyback_yield.map(round2), Some(-10.0));
+        assert_eq!(result.shareholder_yield.map(round2), Some(-2.0));
+        assert!(result.is_dilutive_payer);
+
+        // Most recent year is missing so there is no net buyback yield
+        let reports = with_shares(&[Some(1000.0), Some(1100.0), None]);
+        let result = calculate_shareholder_yield(&reports, Some(8.0));
+        assert_eq!(result.net_buyback_yield, None);
+        assert_eq!(result.shareholder_yield, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_dividend_growth_backed() -> Result<(), String> {
+        let mut growth = GrowthTrends::default();
+        assert_eq!(is_dividend_growth_backed(5.0, &growth), None);
+        growth.eps.cagr = Some(4.0);
+        assert_eq!(is_dividend_growth_backed(5.0, &growth), Some(false));
+        growth.fcf_per_share.cagr = Some(6.0);
+        assert_eq!(is_dividend_growth_backed(5.0, &growth), Some(true));
+        Ok(())
+    }
+}
@@ -0,0 +1,329 @@
+use crate::forecast::ForecastParams;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How dividends (and optionally share price) grow over years of forecast. Rates are given as
+/// fractions e.g. 0.05 for 5%
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum GrowthModel {
+    /// Dividend growth of forecast params forever
+    #[default]
+    Constant,
+    /// Dividend growth moves linearly from forecast params one to long term rate within
+    /// given number of years
+    LinearFade { long_term: f64, years: u32 },
+    /// Gap between dividend growth and long term rate halves every half life years
+    ExponentialFade { long_term: f64, half_life: f64 },
+    /// Dividend growth of consecutive periods given as (rate, number of years), final rate
+    /// is used afterwards
+    Step {
+        schedule: Vec<(f64, u32)>,
+        final_rate: f64,
+    },
+    /// Constant dividend growth fitted (log-linear) into annual dividends of history
+    Regression,
+    /// Share price grows as dividends do, so Div Yield stays constant
+    YieldConstant,
+}
+
+/// (year, annual dividends) of full years of dividend history [(YYYY-MM-DD, amount)], oldest
+/// first. First and last year of history are skipped as they may be partial. Years in between
+/// with no dividends paid are kept with zero amount
+pub fn annual_dividends(div_history: &[(String, f64)]) -> Vec<(i32, f64)> {
+    let last_year = div_history
+        .iter()
+        .filter_map(|(date, _)| date.get(..4)?.parse::<i32>().ok())
+        .max();
+    last_year.map_or(vec![], |last_year| {
+        annual_dividends_through(div_history, last_year - 1)
+    })
+}
+
+/// (year, annual dividends) of dividend history from the year after first year of history (it
+/// may be partial) through given year, oldest first. Years with no dividends paid are kept with
+/// zero amount
+pub fn annual_dividends_through(div_history: &[(String, f64)], last_year: i32) -> Vec<(i32, f64)> {
+    let mut annual: BTreeMap<i32, f64> = BTreeMap::new();
+    div_history
+        .iter()
+        .filter_map(|(date, amount)| Some((date.get(..4)?.parse::<i32>().ok()?, amount)))
+        .filter(|(year, _)| *year <= last_year)
+        .for_each(|(year, amount)| *annual.entry(year).or_insert(0.0) += amount);
+    let Some(first) = annual.keys().next() else {
+        return vec![];
+    };
+    (first + 1..=last_year)
+        .map(|year| (year, annual.get(&year).copied().unwrap_or(0.0)))
+        .collect()
+}
+
+/// Annual growth rate of least squares line fitted into logarithms of annual dividends
+pub fn regression_growth(div_history: &[(String, f64)]) -> Option<f64> {
+    let annual = annual_dividends(div_history);
+    if annual.len() < 2 || annual.iter().any(|(_, d)| *d <= 0.0) {
+        return None;
+    }
+    let logs: Vec<(f64, f64)> = annual.iter().map(|(y, d)| (*y as f64, d.ln())).collect();
+    crate::growth::linear_slope(&logs).map(|slope| slope.exp() - 1.0)
+}
+
+fn parse_rate(rate: &str) -> Result<f64, &'static str> {
+    rate.trim()
+        .parse::<f64>()
+        .map(|r| r / 100.0)
+        .map_err(|_| "Error: Growth rate of growth model is not a number")
+}
+
+impl GrowthModel {
+    /// Growth model of specification (rates[%]):
+    /// "constant", "linear-fade:<long term rate>:<years>", "exp-fade:<long term rate>:<half life>",
+    /// "step:<rate>x<years>,<rate>x<years>,...,<final rate>", "regression", "yield-constant"
+    pub fn from_spec(spec: &str) -> Result<GrowthModel, &'static str> {
+        let mut parts = spec.trim().split(':');
+        let name = parts.next().unwrap_or("").to_lowercase();
+        let args: Vec<&str> = parts.collect();
+        match (name.as_str(), args.as_slice()) {
+            ("constant", []) => Ok(GrowthModel::Constant),
+            ("regression", []) => Ok(GrowthModel::Regression),
+            ("yield-constant", []) => Ok(GrowthModel::YieldConstant),
+            ("linear-fade", [long_term, years]) => Ok(GrowthModel::LinearFade {
+                long_term: parse_rate(long_term)?,
+                years: years
+                    .trim()
+                    .parse()
+                    .map_err(|_| "Error: Fade years should be a whole number")?,
+            }),
+            ("exp-fade", [long_term, half_life]) => Ok(GrowthModel::ExponentialFade {
+                long_term: parse_rate(long_term)?,
+                half_life: half_life
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|h: &f64| *h > 0.0)
+                    .ok_or("Error: Half life should be a positive number")?,
+            }),
+            ("step", [steps]) => {
+                let mut steps: Vec<&str> = steps.split(',').collect();
+                let final_rate = parse_rate(steps.pop().unwrap_or(""))?;
+                let schedule = steps
+                    .iter()
+                    .map(|step| {
+                        let (rate, years) = step
+                            .split_once('x')
+                            .ok_or("Error: Step should be given as <rate>x<years>")?;
+                        let years = years
+                            .trim()
+                            .parse()
+                            .map_err(|_| "Error: Step years should be a whole number")?;
+                        Ok((parse_rate(rate)?, years))
+                    })
+                    .collect::<Result<Vec<(f64, u32)>, &'static str>>()?;
+                Ok(GrowthModel::Step {
+                    schedule,
+                    final_rate,
+                })
+            }
+            _ => {
+                log::error!("Unknown growth model: {spec}");
+                Err("Error: Unknown growth model. Possible values: constant, linear-fade:<rate>:<years>, exp-fade:<rate>:<half life>, step:<rate>x<years>,...,<rate>, regression, yield-constant")
+            }
+        }
+    }
+
+    /// Dividend growth of given year of forecast (1 is the first one)
+    fn div_growth(&self, initial: f64, year: u32) -> f64 {
+        match self {
+            GrowthModel::LinearFade { long_term, years } => {
+                let progress = if *years == 0 {
+                    1.0
+                } else {
+                    ((year - 1) as f64 / *years as f64).min(1.0)
+                };
+                initial + (long_term - initial) * progress
+            }
+            GrowthModel::ExponentialFade {
+                long_term,
+                half_life,
+            } => long_term + (initial - long_term) * 0.5f64.powf((year - 1) as f64 / half_life),
+            GrowthModel::Step {
+                schedule,
+                final_rate,
+            } => {
+                let mut end = 0;
+                schedule
+                    .iter()
+                    .find(|(_, years)| {
+                        end += years;
+                        year <= end
+                    })
+                    .map_or(*final_rate, |(rate, _)| *rate)
+            }
+            _ => initial,
+        }
+    }
+
+    /// (share price growth, dividend growth) of consecutive years of forecast. Regression
+    /// model falls back to dividend growth of params if history is too short
+    pub fn annual_rates(
+        &self,
+        params: &ForecastParams,
+        div_history: &[(String, f64)],
+        num_years: u32,
+    ) -> Vec<(f64, f64)> {
+        let initial = match self {
+            GrowthModel::Regression => regression_growth(div_history).unwrap_or_else(|| {
+                log::warn!("Not enough dividend history for regression, DGR is used instead");
+                params.div_growth
+            }),
+            _ => params.div_growth,
+        };
+        (1..=num_years)
+            .map(|year| {
+                let div_growth = self.div_growth(initial, year);
+                match self {
+                    GrowthModel::YieldConstant => (div_growth, div_growth),
+                    _ => (params.share_price_growth_rate, div_growth),
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for GrowthModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrowthModel::Constant => write!(f, "constant DGR"),
+            GrowthModel::LinearFade { long_term, years } => write!(
+                f,
+                "DGR fading linearly to {:.2}% in {years} years",
+                long_term * 100.0
+            ),
+            GrowthModel::ExponentialFade {
+                long_term,
+                half_life,
+            } => write!(
+                f,
+                "DGR fading to {:.2}% with half life of {half_life} years",
+                long_term * 100.0
+            ),
+            GrowthModel::Step {
+                schedule,
+                final_rate,
+            } => {
+                let steps: Vec<String> = schedule
+                    .iter()
+                    .map(|(rate, years)| format!("{:.2}% for {years} years", rate * 100.0))
+                    .collect();
+                write!(
+                    f,
+                    "DGR of {}, then {:.2}%",
+                    steps.join(", "),
+                    final_rate * 100.0
+                )
+            }
+            GrowthModel::Regression => write!(f, "DGR of regression on dividend history"),
+            GrowthModel::YieldConstant => write!(f, "constant Div Yield"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::Reinvestment;
+
+    fn params() -> ForecastParams {
+        ForecastParams {
+            base_capital: 1000.0,
+            div_yield: 0.03,
+            div_growth: 0.2,
+            share_price: 100.0,
+            share_price_growth_rate: 0.07,
+            tax_rate: 0.0,
+            num_capitalizations: 4,
+            reinvestment: Reinvestment::None,
+            contributions: None,
+        }
+    }
+
+    fn rounded(rates: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+        let round = |r: f64| (r * 10000.0).round() / 10000.0;
+        rates.iter().map(|(p, d)| (round(*p), round(*d))).collect()
+    }
+
+    #[test]
+    fn test_from_spec() -> Result<(), String> {
+        assert_eq!(GrowthModel::from_spec("constant")?, GrowthModel::Constant);
+        assert_eq!(
+            GrowthModel::from_spec("linear-fade:4:10")?,
+            GrowthModel::LinearFade {
+                long_term: 0.04,
+                years: 10
+            }
+        );
+        assert_eq!(
+            GrowthModel::from_spec("step:12x5,8x5,4")?,
+            GrowthModel::Step {
+                schedule: vec![(0.12, 5), (0.08, 5)],
+                final_rate: 0.04
+            }
+        );
+        assert!(GrowthModel::from_spec("exp-fade:4:0").is_err());
+        assert!(GrowthModel::from_spec("unknown").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_annual_rates() -> Result<(), String> {
+        let fade = GrowthModel::from_spec("linear-fade:4:2")?;
+        assert_eq!(
+            rounded(fade.annual_rates(&params(), &[], 4)),
+            vec![(0.07, 0.2), (0.07, 0.12), (0.07, 0.04), (0.07, 0.04)]
+        );
+        let fade = GrowthModel::from_spec("exp-fade:4:1")?;
+        assert_eq!(
+            rounded(fade.annual_rates(&params(), &[], 3)),
+            vec![(0.07, 0.2), (0.07, 0.12), (0.07, 0.08)]
+        );
+        let step = GrowthModel::from_spec("step:10x1,5x2,2")?;
+        assert_eq!(
+            rounded(step.annual_rates(&params(), &[], 5)),
+            vec![
+                (0.07, 0.1),
+                (0.07, 0.05),
+                (0.07, 0.05),
+                (0.07, 0.02),
+                (0.07, 0.02)
+            ]
+        );
+        assert_eq!(
+            rounded(GrowthModel::YieldConstant.annual_rates(&params(), &[], 2)),
+            vec![(0.2, 0.2), (0.2, 0.2)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_regression_growth() -> Result<(), String> {
+        // Partial 2019 and 2023 are skipped, dividends grow 10% a year in between
+        let div_history: Vec<(String, f64)> = vec![
+            ("2019-12-01", 0.1),
+            ("2020-06-01", 1.0),
+            ("2021-06-01", 1.1),
+            ("2022-06-01", 1.21),
+            ("2023-06-01", 0.5),
+        ]
+        .into_iter()
+        .map(|(d, a)| (d.to_owned(), a))
+        .collect();
+        assert_eq!(
+            regression_growth(&div_history).map(|g| (g * 10000.0).round() / 10000.0),
+            Some(0.1)
+        );
+        assert_eq!(
+            rounded(GrowthModel::Regression.annual_rates(&params(), &div_history[..2], 1)),
+            vec![(0.07, 0.2)]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+
+/// Inflation derived from consumer price index series (e.g. US CPI-U, Polish CPI)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpiSeries {
+    /// Year over year inflation[%] by date of observation, oldest first
+    rates: Vec<(NaiveDate, f64)>,
+}
+
+/// Date given as YYYY-MM-DD or YYYY-MM (monthly series)
+fn parse_date(date: &str) -> Option<NaiveDate> {
+    let date = date.trim();
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{date}-01"), "%Y-%m-%d"))
+        .ok()
+}
+
+impl CpiSeries {
+    /// Series of index levels. Inflation of an observation is its change against observation
+    /// of the same month a year before
+    pub fn from_index(observations: &[(NaiveDate, f64)]) -> Self {
+        let mut rates: Vec<(NaiveDate, f64)> = observations
+            .iter()
+            .filter_map(|(date, level)| {
+                observations
+                    .iter()
+                    .find(|(d, _)| d.year() == date.year() - 1 && d.month() == date.month())
+                    .filter(|(_, previous)| *previous > 0.0)
+                    .map(|(_, previous)| (*date, (level / previous - 1.0) * 100.0))
+            })
+            .collect();
+        rates.sort_by(|a, b| a.0.cmp(&b.0));
+        CpiSeries { rates }
+    }
+
+    /// Series of year over year inflation rates[%]
+    pub fn from_rates(rates: &[(NaiveDate, f64)]) -> Self {
+        let mut rates = rates.to_vec();
+        rates.sort_by(|a, b| a.0.cmp(&b.0));
+        CpiSeries { rates }
+    }
+
+    /// Load series from CSV with "Date" column and either "CPI" column of index levels or
+    /// "Inflation" column of year over year rates[%]
+    pub fn from_csv(path: &str) -> Result<Self, &'static str> {
+        let df = CsvReader::from_path(path)
+            .map_err(|e| {
+                log::error!("Unable to open {path}: {e}");
+                "Error: Unable to open CPI file"
+            })?
+            .has_header(true)
+            .finish()
+            .map_err(|e| {
+                log::error!("Unable to read {path}: {e}");
+                "Error: Unable to read CPI file"
+            })?;
+        let dates: Vec<Option<NaiveDate>> = df
+            .column("Date")
+            .map_err(|_| "Error: Date column does not exist in CPI file")?
+            .cast(&DataType::Utf8)
+            .map_err(|_| "Error: Date column of CPI file is not a text")?
+            .utf8()
+            .map_err(|_| "Error: Date column of CPI file is not a text")?
+            .into_iter()
+            .map(|d| d.and_then(parse_date))
+            .collect();
+        let values = |name: &str| -> Result<Vec<(NaiveDate, f64)>, &'static str> {
+            let values = df
+                .column(name)
+                .and_then(|c| c.cast(&DataType::Float64))
+                .map_err(|_| "Error: CPI values are not numbers")?;
+            Ok(dates
+                .iter()
+                .zip(
+                    values
+                        .f64()
+                        .map_err(|_| "Error: CPI values are not numbers")?,
+                )
+                .filter_map(|(date, value)| Some(((*date)?, value?)))
+                .collect())
+        };
+
+        let series = if df.column("CPI").is_ok() {
+            CpiSeries::from_index(&values("CPI")?)
+        } else if df.column("Inflation").is_ok() {
+            CpiSeries::from_rates(&values("Inflation")?)
+        } else {
+            log::error!("{path} has neither CPI nor Inflation column");
+            return Err("Error: CPI file should have CPI or Inflation column");
+        };
+        if series.rates.is_empty() {
+            log::error!("No inflation could be derived from {path}");
+            return Err("Error: Not enough CPI data");
+        }
+        Ok(series)
+    }
+
+    /// Most recent year over year inflation[%] and its date
+    pub fn trailing_inflation(&self) -> Option<(NaiveDate, f64)> {
+        self.rates.last().copied()
+    }
+
+    /// Average year over year inflation[%] of observations within given year
+    pub fn annual_inflation(&self, year: i32) -> Option<f64> {
+        let rates: Vec<f64> = self
+            .rates
+            .iter()
+            .filter(|(date, _)| date.year() == year)
+            .map(|(_, rate)| *rate)
+            .collect();
+        (!rates.is_empty()).then(|| rates.iter().sum::<f64>() / rates.len() as f64)
+    }
+
+    /// Inflation[%] of consecutive years starting from given one. Years not covered by the series
+    /// (e.g. future ones) are assumed to have trailing inflation
+    pub fn inflation_path(&self, first_year: i32, years: u32) -> Vec<f64> {
+        let trailing = self.trailing_inflation().map_or(0.0, |(_, rate)| rate);
+        (0..years as i32)
+            .map(|i| self.annual_inflation(first_year + i).unwrap_or(trailing))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, 1).unwrap()
+    }
+
+    #[test]
+    fn test_from_index() -> Result<(), String> {
+        let series = CpiSeries::from_index(&[
+            (date(2022, 1), 200.0),
+            (date(2022, 2), 201.0),
+            (date(2023, 1), 210.0),
+            (date(2023, 2), 209.04),
+        ]);
+        let (as_of, rate) = series.trailing_inflation().unwrap();
+        assert_eq!(as_of, date(2023, 2));
+        assert_eq!((rate * 100.0).round() / 100.0, 4.0);
+        // (5% + 4%) / 2
+        assert_eq!(
+            series
+                .annual_inflation(2023)
+                .map(|r| (r * 100.0).round() / 100.0),
+            Some(4.5)
+        );
+        assert_eq!(series.annual_inflation(2022), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inflation_path() -> Result<(), String> {
+        let series = CpiSeries::from_rates(&[(date(2025, 1), 3.0), (date(2024, 1), 6.0)]);
+        assert_eq!(series.trailing_inflation(), Some((date(2025, 1), 3.0)));
+        assert_eq!(series.inflation_path(2024, 3), vec![6.0, 3.0, 3.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_csv() -> Result<(), String> {
+        let path = crate::TempPath::new("cpi.csv");
+        let path = path.as_str();
+        std::fs::write(path, "Date,Inflation\n2024-01,3.1\n2024-02,3.2\n")
+            .map_err(|e| e.to_string())?;
+        let series = CpiSeries::from_csv(path)?;
+        assert_eq!(series.trailing_inflation(), Some((date(2024, 2), 3.2)));
+        Ok(())
+    }
+}
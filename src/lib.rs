@@ -8,6 +8,23 @@ use polygon_client::rest::RESTClient;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 
+pub mod backtest;
+pub mod benchmark;
+pub mod export;
+pub mod forecast;
+pub mod growth;
+pub mod growthmodel;
+pub mod inflation;
+pub mod montecarlo;
+pub mod portfolio;
+pub mod ranking;
+pub mod report;
+pub mod safety;
+pub mod screening;
+pub mod sector;
+pub mod tax;
+pub mod watchlist;
+
 pub fn load_list<R>(excel: &mut Xlsx<R>, category: &str) -> Result<DataFrame, &'static str>
 where
     R: std::io::BufRead,
@@ -259,10 +276,11 @@ pub fn get_polygon_companies_list() -> Result<Vec<(String, Option<String>)>, &'s
         })
 }
 
+/// Get SIC code and SIC description of a company
 async fn get_company_details(
     client: &RESTClient,
     company: &str,
-) -> Result<Option<String>, &'static str> {
+) -> Result<(Option<String>, Option<String>), &'static str> {
     let mut resp = polygon_client::types::ReferenceTickerDetailsResponse {
         request_id: "".to_owned(),
         results: Default::default(),
@@ -277,13 +295,17 @@ async fn get_company_details(
         log::info!("RESPONSE(COMPANY DETAILS): {maybe_resp:#?}");
         (resp, run) = match should_try_again(maybe_resp, resp) {
             Ok((lresp, lrun)) => (lresp, lrun),
-            Err(_) => return Ok(None),
+            Err(_) => return Ok((None, None)),
         };
     }
 
-    Ok(resp.results.sic_description)
+    Ok((resp.results.sic_code, resp.results.sic_description))
 }
 
+/// Get current dividend, average DGR of recent five years, consecutive years of dividend growth
+/// and complete history of paid cash dividends (pay date, amount) ordered from older to newer.
+/// Only current dividend and DGR are limited to recent five years, the history is not filtered so
+/// dividend cuts, trailing yields, growth estimates and backtests can look further back
 async fn get_dividiend_data(
     client: &RESTClient,
     query_params: &HashMap<&str, &str>,
@@ -363,8 +385,9 @@ async fn get_dividiend_data(
 
     let current_year = Utc::now().year();
     let num_years_of_interest = 5;
-    let div_history = div_history
-        .into_iter()
+    let recent_div_history = div_history
+        .iter()
+        .cloned()
         .filter(|x| {
             let x_date_year = NaiveDate::parse_from_str(&x.0, "%Y-%m-%d")
                 .expect("unable to parse date")
@@ -378,7 +401,7 @@ async fn get_dividiend_data(
         .collect::<Vec<_>>();
 
     // Curr Dividend  and corressponding date
-    let (curr_div, curr_div_date) = match div_history.iter().rev().next() {
+    let (curr_div, curr_div_date) = match recent_div_history.iter().rev().next() {
         Some((pay_date, cash_amount)) => (
             Some(*cash_amount),
             Some(
@@ -396,28 +419,212 @@ async fn get_dividiend_data(
         None
     };
 
-    let dgr = calculate_dgr(&div_history, Utc::now().year().to_string().as_ref())?;
+    let dgr = calculate_dgr(&recent_div_history, Utc::now().year().to_string().as_ref())?;
     log::info!("Current Div: {curr_div:?} {currency:?}, Paid date: {curr_div_date:?}, Average DGR(samples: {}): {dgr:?}",
-            div_history.len());
+            recent_div_history.len());
 
     Ok((curr_div, dgr, years_of_growth, div_history))
 }
 
-pub fn get_polygon_data(
-    company: &str,
-) -> Result<
-    (
-        f64,
-        Option<f64>,
-        Option<f64>,
-        Option<u32>,
-        Option<f64>,
-        Option<u32>,
-        Option<f64>,
-        Option<String>,
-    ),
-    &'static str,
-> {
+/// Get history of paid cash dividends (pay date, amount) of a company ordered from older to
+/// newer, without other data of the company
+pub fn get_polygon_div_history(company: &str) -> Result<Vec<(String, f64)>, &'static str> {
+    let mut query_params = HashMap::new();
+    query_params.insert("ticker", company);
+
+    let client = RESTClient::new(None, None);
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(async {
+            let (_, _, _, div_history) = get_dividiend_data(&client, &query_params).await?;
+            Ok(div_history)
+        })
+}
+
+/// Figures of interest extracted from a single financial report (Polygon.io financials vX)
+#[derive(Debug, Clone, Default)]
+pub struct FinancialReport {
+    pub fiscal_year: String,
+    /// "FY" for annual reports, "Q1"-"Q4" for quarterly ones
+    pub fiscal_period: String,
+    pub end_date: Option<String>,
+    pub revenues: Option<f64>,
+    pub operating_cash_flow: Option<f64>,
+    pub investing_cash_flow: Option<f64>,
+    pub basic_average_shares: Option<f64>,
+    pub basic_eps: Option<f64>,
+    pub net_income: Option<f64>,
+    pub operating_income: Option<f64>,
+    pub interest_expense: Option<f64>,
+    pub depreciation_and_amortization: Option<f64>,
+    pub liabilities: Option<f64>,
+    pub equity: Option<f64>,
+    /// Equity excluding noncontrolling interest i.e. owned by shareholders of the company
+    pub equity_attributable_to_parent: Option<f64>,
+    pub current_assets: Option<f64>,
+    pub current_liabilities: Option<f64>,
+    pub long_term_debt: Option<f64>,
+    pub cash: Option<f64>,
+    /// Dividend payout ratio[%] of this fiscal year
+    pub payout_ratio: Option<f64>,
+}
+
+impl FinancialReport {
+    fn per_share(&self, value: Option<f64>) -> Option<f64> {
+        match (value, self.basic_average_shares) {
+            (Some(value), Some(num_shares)) if num_shares > 0.0 => Some(value / num_shares),
+            _ => None,
+        }
+    }
+
+    /// Earnings per share as reported or derived from net income
+    pub fn eps(&self) -> Option<f64> {
+        self.basic_eps.or_else(|| self.per_share(self.net_income))
+    }
+
+    /// Approximation of free cash flow: operating cash flow plus (usually negative) investing
+    /// cash flow. Capital expenditures are not reported separately, so investing cash flow
+    /// stands in for them and also includes acquisitions and purchases of investments.
+    /// None if investing cash flow is not reported
+    pub fn free_cash_flow(&self) -> Option<f64> {
+        match (self.operating_cash_flow, self.investing_cash_flow) {
+            (Some(operating), Some(investing)) => Some(operating + investing),
+            (Some(_), None) => {
+                log::warn!(
+                    "Fiscal year: {} no investing cash flow, free cash flow unavailable",
+                    self.fiscal_year
+                );
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn fcf_per_share(&self) -> Option<f64> {
+        self.per_share(self.free_cash_flow())
+    }
+
+    /// Book value per share of equity attributable to parent, as noncontrolling interest
+    /// does not belong to shareholders of the company
+    pub fn book_value_per_share(&self) -> Option<f64> {
+        self.per_share(self.equity_attributable_to_parent)
+    }
+}
+
+/// Valuation of a company based on its share price and most recent annual report
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValuationMetrics {
+    /// Earnings per share[$]
+    pub eps: Option<f64>,
+    /// Price to earnings
+    pub pe: Option<f64>,
+    /// Price to free cash flow (approximated with operating plus investing cash flow)
+    pub pfcf: Option<f64>,
+    /// Book value per share[$]
+    pub bvps: Option<f64>,
+    /// Price to book value
+    pub pb: Option<f64>,
+    /// Benjamin Graham's fair price estimate[$]
+    pub graham_number: Option<f64>,
+}
+
+/// Balance sheet health (leverage and liquidity) based on most recent annual report
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthMetrics {
+    /// Total liabilities to equity
+    pub debt_to_equity: Option<f64>,
+    pub net_debt_to_ebitda: Option<f64>,
+    /// Operating income to interest expense
+    pub interest_coverage: Option<f64>,
+    /// Current assets to current liabilities
+    pub current_ratio: Option<f64>,
+}
+
+/// Company data as acquired from Polygon.io API
+#[derive(Debug, Clone, Default)]
+pub struct PolygonData {
+    pub share_price: f64,
+    pub curr_div: Option<f64>,
+    /// Dividend Yield[%]
+    pub divy: Option<f64>,
+    pub frequency: Option<u32>,
+    /// Average dividend growth rate of recent five years[%]
+    pub dgr: Option<f64>,
+    pub years_of_growth: Option<u32>,
+    /// Payout ratio[%] of the most recent fiscal year
+    pub payout_ratio: Option<f64>,
+    pub sic_code: Option<String>,
+    /// SIC description
+    pub sector_desc: Option<String>,
+    /// Paid cash dividends (pay date, amount) ordered from older to newer
+    pub div_history: Vec<(String, f64)>,
+    /// Annual financial reports ordered from older to newer
+    pub annual_reports: Vec<FinancialReport>,
+    /// Quarterly financial reports ordered from older to newer
+    pub quarterly_reports: Vec<FinancialReport>,
+}
+
+impl PolygonData {
+    /// Inputs for dividend safety score computation
+    pub fn safety_inputs(&self) -> safety::SafetyInputs {
+        safety::SafetyInputs {
+            payout_ratio: self.payout_ratio,
+            payout_history: self
+                .annual_reports
+                .iter()
+                .filter_map(|r| r.payout_ratio)
+                .collect(),
+            dividend_cuts: calculate_dividend_cuts(
+                &self.div_history,
+                Utc::now().year().to_string().as_ref(),
+            )
+            .unwrap_or(None),
+            years_of_growth: self.years_of_growth,
+            debt_to_equity: self.health().debt_to_equity,
+            operating_cash_flows: self
+                .annual_reports
+                .iter()
+                .filter_map(|r| r.operating_cash_flow)
+                .collect(),
+            div_yield: self.divy,
+        }
+    }
+
+    /// Leverage and liquidity based on most recent annual report
+    pub fn health(&self) -> HealthMetrics {
+        match self.annual_reports.last() {
+            Some(report) => calculate_health(report),
+            None => HealthMetrics::default(),
+        }
+    }
+
+    /// Sector and industry of common taxonomy based on SIC code and description
+    pub fn sector(&self) -> (sector::Sector, Option<String>) {
+        sector::classify_sic(self.sic_code.as_deref(), self.sector_desc.as_deref())
+    }
+
+    /// Growth of revenue, EPS, operating cash flow and free cash flow per share
+    pub fn growth(&self) -> growth::GrowthTrends {
+        growth::calculate_growth_trends(&self.annual_reports, &self.quarterly_reports)
+    }
+
+    /// Share count trend (buybacks or dilution) and shareholder yield
+    pub fn shareholder_yield(&self) -> growth::ShareholderYield {
+        growth::calculate_shareholder_yield(&self.annual_reports, self.divy)
+    }
+
+    /// Valuation metrics based on previous close share price and most recent annual report
+    pub fn valuation(&self) -> ValuationMetrics {
+        match self.annual_reports.last() {
+            Some(report) => calculate_valuation(self.share_price, report),
+            None => ValuationMetrics::default(),
+        }
+    }
+}
+
+pub fn get_polygon_data(company: &str) -> Result<PolygonData, &'static str> {
     let mut query_params = HashMap::new();
     query_params.insert("ticker", company);
 
@@ -431,7 +638,16 @@ pub fn get_polygon_data(
             let (curr_div, dgr, years_of_growth, div_history) =
                 get_dividiend_data(&client, &query_params).await?;
 
-            let sector_desc = get_company_details(&client, company).await?;
+            let (sic_code, sector_desc) = get_company_details(&client, company).await?;
+
+            let mut data = PolygonData {
+                curr_div,
+                dgr,
+                years_of_growth,
+                sic_code,
+                sector_desc,
+                ..Default::default()
+            };
 
             let mut close_query_params = HashMap::new();
             close_query_params.insert("adjusted", "true");
@@ -454,28 +670,8 @@ pub fn get_polygon_data(
                 (resp, run) = match should_try_again(maybe_resp, resp) {
                     Ok((resp, run)) => (resp, run),
                     Err(_) => {
-                        return Ok::<
-                            (
-                                f64,
-                                Option<f64>,
-                                Option<f64>,
-                                Option<u32>,
-                                Option<f64>,
-                                Option<u32>,
-                                Option<f64>,
-                                Option<String>,
-                            ),
-                            &'static str,
-                        >((
-                            0.0,
-                            curr_div,
-                            None,
-                            None,
-                            dgr,
-                            years_of_growth,
-                            None,
-                            sector_desc,
-                        ))
+                        data.div_history = div_history;
+                        return Ok::<PolygonData, &'static str>(data);
                     }
                 };
             }
@@ -492,30 +688,11 @@ pub fn get_polygon_data(
                 }
                 None => {
                     log::info!("No stock price data found");
-                    return Ok::<
-                        (
-                            f64,
-                            Option<f64>,
-                            Option<f64>,
-                            Option<u32>,
-                            Option<f64>,
-                            Option<u32>,
-                            Option<f64>,
-                            Option<String>,
-                        ),
-                        &'static str,
-                    >((
-                        0.0,
-                        curr_div,
-                        None,
-                        None,
-                        dgr,
-                        years_of_growth,
-                        None,
-                        sector_desc,
-                    ));
+                    data.div_history = div_history;
+                    return Ok::<PolygonData, &'static str>(data);
                 }
             };
+            data.share_price = share_price;
 
             let divy = calculate_divy(
                 &div_history,
@@ -523,6 +700,7 @@ pub fn get_polygon_data(
                 Utc::now().year().to_string().as_ref(),
             )?;
             log::info!("Stock price: {share_price}, Div Yield[%]: {divy:.2?}");
+            data.divy = divy;
 
             let (annuallized_div, frequency) = match calculate_annualized_div(
                 &div_history,
@@ -532,109 +710,227 @@ pub fn get_polygon_data(
                 None => (None, None),
             };
             log::info!("Annualized dividend: {annuallized_div:?}, annual frequency: {frequency:?}");
+            data.frequency = frequency;
 
-            run = true;
-            let mut resp = polygon_client::types::ReferenceStockFinancialsVXResponse {
-                next_url: None,
-                results: vec![],
-                status: "OK".to_owned(),
-                request_id: None,
+            let resp = match get_financials(&client, company, "annual", NUM_ANNUAL_REPORTS).await {
+                Ok(resp) => resp,
+                Err(_) => {
+                    data.div_history = div_history;
+                    return Ok::<PolygonData, &'static str>(data);
+                }
             };
-            while run {
-                let maybe_resp = client.reference_stock_financials_vx(&query_params).await;
-                log::info!("RESPONSE(STOCK FINANCIALS): {maybe_resp:#?}");
-                (resp, run) = match should_try_again(maybe_resp, resp) {
-                    Ok((resp, run)) => (resp, run),
-                    Err(_) => {
-                        return Ok::<
-                            (
-                                f64,
-                                Option<f64>,
-                                Option<f64>,
-                                Option<u32>,
-                                Option<f64>,
-                                Option<u32>,
-                                Option<f64>,
-                                Option<String>,
-                            ),
-                            &'static str,
-                        >((
-                            share_price,
-                            curr_div,
-                            divy,
-                            frequency,
-                            dgr,
-                            years_of_growth,
-                            None,
-                            sector_desc,
-                        ))
-                    }
-                };
-            }
+            match get_financials(&client, company, "quarterly", NUM_QUARTERLY_REPORTS).await {
+                Ok(quarterly_resp) => {
+                    data.quarterly_reports =
+                        get_financial_reports(&quarterly_resp, "quarterly", &div_history)?
+                }
+                Err(e) => log::warn!("{company}: unable to get quarterly financials: {e}"),
+            };
+
+            data.payout_ratio = get_annual_payout_rate(&resp, &div_history)?;
+            data.annual_reports = get_financial_reports(&resp, "annual", &div_history)?;
+            data.div_history = div_history;
+
+            return Ok::<PolygonData, &'static str>(data);
+        })
+}
+
+/// Number of most recent annual financial reports to get trends from
+const NUM_ANNUAL_REPORTS: usize = 10;
+/// Number of most recent quarterly financial reports to get trends from
+const NUM_QUARTERLY_REPORTS: usize = 8;
+
+/// Get most recent financial reports of given timeframe ("annual" or "quarterly"),
+/// following next pages until requested number of reports is collected
+async fn get_financials(
+    client: &RESTClient,
+    company: &str,
+    timeframe: &str,
+    num_reports: usize,
+) -> Result<polygon_client::types::ReferenceStockFinancialsVXResponse, &'static str> {
+    let limit = num_reports.to_string();
+    let mut query_params = HashMap::new();
+    query_params.insert("ticker", company);
+    query_params.insert("timeframe", timeframe);
+    query_params.insert("sort", "period_of_report_date");
+    query_params.insert("order", "desc");
+    query_params.insert("limit", limit.as_str());
+
+    let mut run = true;
+    let mut resp = polygon_client::types::ReferenceStockFinancialsVXResponse {
+        next_url: None,
+        results: vec![],
+        status: "OK".to_owned(),
+        request_id: None,
+    };
+    while run {
+        let maybe_resp = client.reference_stock_financials_vx(&query_params).await;
+        log::info!("RESPONSE(STOCK FINANCIALS): {maybe_resp:#?}");
+        (resp, run) = should_try_again(maybe_resp, resp)?;
+    }
 
-            let payout_rate = get_annual_payout_rate(&resp, &div_history)?;
-
-            return Ok::<
-                (
-                    f64,
-                    Option<f64>,
-                    Option<f64>,
-                    Option<u32>,
-                    Option<f64>,
-                    Option<u32>,
-                    Option<f64>,
-                    Option<String>,
+    let mut results = std::mem::take(&mut resp.results);
+    while results.len() < num_reports {
+        let Some(url) = resp.next_url.clone() else {
+            break;
+        };
+        run = true;
+        while run {
+            let maybe_resp = client.fetch_next_page(&url).await;
+            log::info!("RESPONSE NEXT PAGE (STOCK FINANCIALS): {maybe_resp:#?}");
+            (resp, run) = should_try_again(maybe_resp, resp)?;
+        }
+        results.append(&mut resp.results);
+    }
+    results.truncate(num_reports);
+    resp.results = results;
+    Ok(resp)
+}
+
+/// Extract figures of interest from financial reports of given timeframe, ordered from older
+/// to newer. Payout ratio is computed for annual reports only
+fn get_financial_reports(
+    resp: &polygon_client::types::ReferenceStockFinancialsVXResponse,
+    timeframe: &str,
+    div_history: &Vec<(String, f64)>,
+) -> Result<Vec<FinancialReport>, &'static str> {
+    let mut reports = resp
+        .results
+        .iter()
+        .filter(|x| x.timeframe == timeframe && x.end_date.is_some())
+        .map(|r| {
+            let company_name: &str = r.company_name.as_ref();
+            let fiscal_year: &str = r.fiscal_year.as_ref();
+            let fiscal_period: &str = r.fiscal_period.as_ref();
+
+            let value = |statement: Statement, field: &str| {
+                get_financial_value(
+                    &r.financials,
+                    statement,
+                    field,
+                    company_name,
+                    fiscal_year,
+                    fiscal_period,
+                )
+            };
+
+            let operating_cash_flow =
+                get_net_cash_flow(&r.financials, company_name, fiscal_year, fiscal_period)?;
+            let basic_average_shares =
+                get_basic_average_shares(&r.financials, company_name, fiscal_year, fiscal_period)?;
+            let annuallized_div = if timeframe == "annual" {
+                match calculate_annualized_div(div_history, fiscal_year)? {
+                    Some((x, _)) => Some(x),
+                    None => None,
+                }
+            } else {
+                None
+            };
+            let payout_ratio = match (basic_average_shares, annuallized_div, operating_cash_flow) {
+                (Some(num_shares), Some(annuallized_div), Some(net_value)) => Some(
+                    calculate_payout_ratio(annuallized_div, num_shares, net_value)?,
                 ),
-                &'static str,
-            >((
-                share_price,
-                curr_div,
-                divy,
-                frequency,
-                dgr,
-                years_of_growth,
-                payout_rate,
-                sector_desc,
-            ));
+                _ => None,
+            };
+
+            Ok::<FinancialReport, &'static str>(FinancialReport {
+                fiscal_year: r.fiscal_year.clone(),
+                fiscal_period: r.fiscal_period.clone(),
+                end_date: r.end_date.clone(),
+                revenues: value(Statement::IncomeStatement, "revenues"),
+                operating_cash_flow,
+                investing_cash_flow: value(
+                    Statement::CashFlow,
+                    "net_cash_flow_from_investing_activities",
+                ),
+                basic_average_shares,
+                basic_eps: value(Statement::IncomeStatement, "basic_earnings_per_share"),
+                net_income: value(Statement::IncomeStatement, "net_income_loss"),
+                operating_income: value(Statement::IncomeStatement, "operating_income_loss"),
+                interest_expense: value(Statement::IncomeStatement, "interest_expense_operating"),
+                depreciation_and_amortization: value(
+                    Statement::IncomeStatement,
+                    "depreciation_and_amortization",
+                ),
+                liabilities: value(Statement::BalanceSheet, "liabilities"),
+                equity: value(Statement::BalanceSheet, "equity"),
+                equity_attributable_to_parent: value(
+                    Statement::BalanceSheet,
+                    "equity_attributable_to_parent",
+                ),
+                current_assets: value(Statement::BalanceSheet, "current_assets"),
+                current_liabilities: value(Statement::BalanceSheet, "current_liabilities"),
+                long_term_debt: value(Statement::BalanceSheet, "long_term_debt"),
+                cash: value(Statement::BalanceSheet, "cash"),
+                payout_ratio,
+            })
         })
+        .collect::<Result<Vec<FinancialReport>, &'static str>>()?;
+
+    reports.sort_by(|x, y| {
+        let x_date = NaiveDate::parse_from_str(x.end_date.as_ref().unwrap(), "%Y-%m-%d")
+            .expect("Wrong end date format");
+        let y_date = NaiveDate::parse_from_str(y.end_date.as_ref().unwrap(), "%Y-%m-%d")
+            .expect("Wrong end date format");
+        x_date.cmp(&y_date)
+    });
+    Ok(reports)
 }
 
-fn get_net_cash_flow(
+/// Financial statements as present in Polygon.io financials vX results
+enum Statement {
+    BalanceSheet,
+    IncomeStatement,
+    CashFlow,
+}
+
+/// Get value of given statement's field. Missing statements or fields are reported and
+/// result in None
+fn get_financial_value(
     fd: &polygon_client::types::FinancialDimensions,
+    statement: Statement,
+    field: &str,
     company_name: &str,
     fiscal_year: &str,
     fiscal_period: &str,
-) -> Result<Option<f64>, &'static str> {
-    let net_value = if let Some(ismap) = &fd.cash_flow_statement {
-        let net_value = if ismap.contains_key("net_cash_flow_from_operating_activities") {
-            let net_cash_flow = ismap
-                .get("net_cash_flow_from_operating_activities")
-                .expect("Error getting net_cash_flow_from_operating_activities");
-            let net_value = net_cash_flow.value.clone().unwrap();
-            let net_unit = net_cash_flow.unit.clone().unwrap();
-            let net_label = net_cash_flow.label.clone().unwrap();
-            log::info!(
-                "{}: {} {} net cash flow: {} of {}, labeled as {}",
-                company_name,
-                fiscal_year,
-                fiscal_period,
-                net_value,
-                net_unit,
-                net_label
+) -> Option<f64> {
+    let (statement_map, statement_name) = match statement {
+        Statement::BalanceSheet => (&fd.balance_sheet, "balance sheet"),
+        Statement::IncomeStatement => (&fd.income_statement, "income statement"),
+        Statement::CashFlow => (&fd.cash_flow_statement, "cash flow statement"),
+    };
+    let Some(smap) = statement_map else {
+        log::warn!("{company_name}: {fiscal_year} {fiscal_period} missing {statement_name}");
+        return None;
+    };
+    match smap.get(field).and_then(|x| x.value) {
+        Some(value) => {
+            log::info!("{company_name}: {fiscal_year} {fiscal_period} {field}: {value}");
+            Some(value)
+        }
+        None => {
+            log::warn!(
+                "{company_name}: {fiscal_year} {fiscal_period} missing {field} in {statement_name}"
             );
-
-            // curr_div * num_shares  / net_value
-            Some(net_value)
-        } else {
-            log::info!("Error: Implement missing cash flow operating statement");
             None
-        };
-        net_value
-    } else {
-        log::info!("Error: Implement missing cash flow statement");
-        None
-    };
-    Ok(net_value)
+        }
+    }
+}
+
+fn get_net_cash_flow(
+    fd: &polygon_client::types::FinancialDimensions,
+    company_name: &str,
+    fiscal_year: &str,
+    fiscal_period: &str,
+) -> Result<Option<f64>, &'static str> {
+    Ok(get_financial_value(
+        fd,
+        Statement::CashFlow,
+        "net_cash_flow_from_operating_activities",
+        company_name,
+        fiscal_year,
+        fiscal_period,
+    ))
 }
 
 fn get_basic_average_shares(
@@ -643,33 +939,14 @@ fn get_basic_average_shares(
     fiscal_year: &str,
     fiscal_period: &str,
 ) -> Result<Option<f64>, &'static str> {
-    let basic_average_shares = if let Some(ismap) = &fd.income_statement {
-        let basic_average_shares = if ismap.contains_key("basic_average_shares") {
-            let basic_average_shares = ismap
-                .get("basic_average_shares")
-                .expect("Error getting basic_average_shares");
-            let value = basic_average_shares.value.clone().unwrap();
-            let unit = basic_average_shares.unit.clone().unwrap();
-            let label = basic_average_shares.label.clone().unwrap();
-            log::info!(
-                "{}: {} {} basic average shares: {} of {}, labeled as {}",
-                company_name,
-                fiscal_year,
-                fiscal_period,
-                value,
-                unit,
-                label
-            );
-            Some(value)
-        } else {
-            None
-        };
-        basic_average_shares
-    } else {
-        log::warn!("Implement missing net_cash_flow_continuing");
-        None
-    };
-    Ok(basic_average_shares)
+    Ok(get_financial_value(
+        fd,
+        Statement::IncomeStatement,
+        "basic_average_shares",
+        company_name,
+        fiscal_year,
+        fiscal_period,
+    ))
 }
 
 fn calculate_annualized_div(
@@ -755,6 +1032,52 @@ fn calculate_consecutive_years_of_growth(
     Ok(Some(num_consecutive_years))
 }
 
+/// Calculate number of years in which annual dividend was lower than in preceding year,
+/// not including current year
+fn calculate_dividend_cuts(
+    div_history: &Vec<(String, f64)>,
+    current_year: &str,
+) -> Result<Option<u32>, &'static str> {
+    let current_year = current_year
+        .parse::<i32>()
+        .expect("Unable to parse currrent year");
+    let mut annual_div: BTreeMap<i32, f64> = BTreeMap::new();
+
+    if div_history.len() == 0 {
+        return Ok(None);
+    }
+
+    div_history.iter().try_for_each(|x| {
+        let year = NaiveDate::parse_from_str(&x.0, "%Y-%m-%d")
+            .map_err(|_| "Error parsing dividend year")?
+            .year();
+        // Skip current year (no full data yet)
+        if year != current_year {
+            *annual_div.entry(year).or_insert(0.0) += x.1;
+        }
+        Ok::<(), &str>(())
+    })?;
+
+    let oldest_year = match annual_div.iter().next() {
+        Some((year, _)) => *year,
+        None => {
+            log::info!("No annual dividend data found");
+            return Ok(None);
+        }
+    };
+    // Year without any dividend paid is a cut as well
+    for y in oldest_year..current_year {
+        annual_div.entry(y).or_insert(0.0);
+    }
+
+    let cuts = annual_div
+        .values()
+        .zip(annual_div.values().skip(1))
+        .filter(|(prev, next)| next < prev)
+        .count();
+    Ok(Some(cuts as u32))
+}
+
 fn get_annual_payout_rate(
     resp: &polygon_client::types::ReferenceStockFinancialsVXResponse,
     div_history: &Vec<(String, f64)>,
@@ -898,6 +1221,85 @@ fn get_quaterly_payout_rate(
     Ok(payout_rate)
 }
 
+/// Valuation metrics. Ratios to non-positive earnings, cash flow or book value are
+/// meaningless and result in None
+fn calculate_valuation(share_price: f64, report: &FinancialReport) -> ValuationMetrics {
+    let price_to = |value: Option<f64>| match value {
+        Some(value) if value > 0.0 && share_price > 0.0 => Some(share_price / value),
+        _ => None,
+    };
+
+    let eps = report.eps();
+    let fcfps = report.fcf_per_share();
+    let bvps = report.book_value_per_share();
+    let graham_number = match (eps, bvps) {
+        (Some(eps), Some(bvps)) if eps > 0.0 && bvps > 0.0 => Some((22.5 * eps * bvps).sqrt()),
+        _ => None,
+    };
+
+    let valuation = ValuationMetrics {
+        eps,
+        pe: price_to(eps),
+        pfcf: price_to(fcfps),
+        bvps,
+        pb: price_to(bvps),
+        graham_number,
+    };
+    log::info!(
+        "Fiscal year: {} valuation: {valuation:?}",
+        report.fiscal_year
+    );
+    valuation
+}
+
+/// Balance sheet health metrics. EBITDA is approximated with operating income when
+/// depreciation and amortization is not reported, and missing cash is treated as no cash.
+/// Ratios to non-positive equity, EBITDA or liabilities are meaningless and result in None
+fn calculate_health(report: &FinancialReport) -> HealthMetrics {
+    let ratio = |num: Option<f64>, den: Option<f64>| match (num, den) {
+        (Some(num), Some(den)) if den > 0.0 => Some(num / den),
+        _ => None,
+    };
+
+    let ebitda = match (
+        report.operating_income,
+        report.depreciation_and_amortization,
+    ) {
+        (Some(operating_income), Some(da)) => Some(operating_income + da),
+        (Some(operating_income), None) => {
+            log::warn!(
+                "Fiscal year: {} no depreciation and amortization, EBITDA approximated with operating income",
+                report.fiscal_year
+            );
+            Some(operating_income)
+        }
+        _ => None,
+    };
+    let net_debt = report.long_term_debt.map(|debt| {
+        if report.cash.is_none() {
+            log::warn!(
+                "Fiscal year: {} no cash reported, net debt equals long term debt",
+                report.fiscal_year
+            );
+        }
+        debt - report.cash.unwrap_or(0.0)
+    });
+    // Interest coverage makes sense only for companies paying interest
+    let interest_coverage = ratio(
+        report.operating_income,
+        report.interest_expense.map(|x| x.abs()),
+    );
+
+    let health = HealthMetrics {
+        debt_to_equity: ratio(report.liabilities, report.equity),
+        net_debt_to_ebitda: ratio(net_debt, ebitda),
+        interest_coverage,
+        current_ratio: ratio(report.current_assets, report.current_liabilities),
+    };
+    log::info!("Fiscal year: {} health: {health:?}", report.fiscal_year);
+    health
+}
+
 /// DGR On quaterly basis calculate(make UT)
 fn calculate_payout_ratio(div: f64, num_shares: f64, net_value: f64) -> Result<f64, &'static str> {
     let payout_rate = div * num_shares as f64 / net_value * 100.0;
@@ -1043,6 +1445,33 @@ fn calculate_dgr(
     }
 }
 
+/// Path in temporary directory unique to the test process, removed (file or directory) when
+/// dropped so that concurrent runs do not collide and failed tests leave nothing behind
+#[cfg(test)]
+pub(crate) struct TempPath(String);
+
+#[cfg(test)]
+impl TempPath {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "investments-forecasting-{}-{name}",
+            std::process::id()
+        ));
+        TempPath(path.to_string_lossy().into_owned())
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0).or_else(|_| std::fs::remove_dir_all(&self.0));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1219,6 +1648,93 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_calculate_valuation() -> Result<(), String> {
+        let report = FinancialReport {
+            fiscal_year: "2023".to_owned(),
+            operating_cash_flow: Some(300.0),
+            investing_cash_flow: Some(-100.0),
+            basic_average_shares: Some(100.0),
+            basic_eps: Some(1.6),
+            equity: Some(1200.0),
+            equity_attributable_to_parent: Some(1000.0),
+            ..Default::default()
+        };
+        // EPS: 1.6, FCF/share: (300 - 100)/100 = 2.0, BVPS: 1000/100 = 10.0
+        // Graham number: sqrt(22.5*1.6*10.0) = 18.97
+        let valuation = calculate_valuation(20.0, &report);
+        assert_eq!(valuation.eps, Some(1.6));
+        assert_eq!(valuation.pe.map(round2), Some(12.5));
+        assert_eq!(valuation.pfcf, Some(10.0));
+        assert_eq!(valuation.bvps, Some(10.0));
+        assert_eq!(valuation.pb, Some(2.0));
+        assert_eq!(valuation.graham_number.map(round2), Some(18.97));
+
+        // EPS derived from net income, loss makes P/E and Graham number meaningless
+        let report = FinancialReport {
+            fiscal_year: "2023".to_owned(),
+            basic_average_shares: Some(100.0),
+            operating_cash_flow: Some(300.0),
+            net_income: Some(-50.0),
+            equity_attributable_to_parent: Some(1000.0),
+            ..Default::default()
+        };
+        let valuation = calculate_valuation(20.0, &report);
+        assert_eq!(valuation.eps, Some(-0.5));
+        assert_eq!(valuation.pe, None);
+        // No investing cash flow, so no free cash flow
+        assert_eq!(valuation.pfcf, None);
+        assert_eq!(valuation.graham_number, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_calculate_health() -> Result<(), String> {
+        let report = FinancialReport {
+            fiscal_year: "2023".to_owned(),
+            operating_income: Some(400.0),
+            interest_expense: Some(50.0),
+            depreciation_and_amortization: Some(100.0),
+            liabilities: Some(1500.0),
+            equity: Some(1000.0),
+            current_assets: Some(300.0),
+            current_liabilities: Some(200.0),
+            long_term_debt: Some(1200.0),
+            cash: Some(200.0),
+            ..Default::default()
+        };
+        // Net debt/EBITDA: (1200 - 200)/(400 + 100) = 2.0
+        assert_eq!(
+            calculate_health(&report),
+            HealthMetrics {
+                debt_to_equity: Some(1.5),
+                net_debt_to_ebitda: Some(2.0),
+                interest_coverage: Some(8.0),
+                current_ratio: Some(1.5),
+            }
+        );
+
+        // Negative equity and no interest expense data
+        let report = FinancialReport {
+            fiscal_year: "2023".to_owned(),
+            operating_income: Some(400.0),
+            liabilities: Some(1500.0),
+            equity: Some(-100.0),
+            long_term_debt: Some(800.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            calculate_health(&report),
+            HealthMetrics {
+                debt_to_equity: None,
+                net_debt_to_ebitda: Some(2.0),
+                interest_coverage: None,
+                current_ratio: None,
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_calculate_annualized_div() -> Result<(), String> {
         let div_hists: Vec<(String, f64)> = vec![
@@ -1244,6 +1760,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_calculate_dividend_cuts() -> Result<(), String> {
+        assert_eq!(calculate_dividend_cuts(&vec![], "2024"), Ok(None));
+
+        let div_hists: Vec<(String, f64)> = vec![
+            ("2021-04-01".to_owned(), 1.0),
+            ("2021-10-01".to_owned(), 1.0),
+            ("2022-04-01".to_owned(), 0.5),
+            ("2022-10-01".to_owned(), 0.5),
+            ("2023-04-01".to_owned(), 1.5),
+            ("2023-10-01".to_owned(), 1.5),
+            ("2024-04-01".to_owned(), 0.1),
+        ];
+        // 2.0 -> 1.0 (cut) -> 3.0, current year is skipped
+        assert_eq!(calculate_dividend_cuts(&div_hists, "2024"), Ok(Some(1)));
+
+        // No dividends in 2021 and 2022 is counted as a single cut
+        let div_hists: Vec<(String, f64)> = vec![
+            ("2020-04-01".to_owned(), 1.0),
+            ("2023-04-01".to_owned(), 1.0),
+        ];
+        assert_eq!(calculate_dividend_cuts(&div_hists, "2024"), Ok(Some(1)));
+        Ok(())
+    }
+
     #[test]
     fn test_calculate_consecutive_years_of_growth() -> Result<(), String> {
         let div_hists: Vec<(String, f64)> = vec![];
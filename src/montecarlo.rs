@@ -0,0 +1,334 @@
+use crate::backtest::PriceHistory;
+use crate::forecast::{forecast_dividends_with_rates, ForecastParams};
+use crate::growthmodel::annual_dividends;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use rayon::prelude::*;
+
+/// Paths are sampled every that many days of time line to keep memory usage of thousands of
+/// paths low
+const SAMPLING_PERIOD: usize = 30;
+
+/// Normal distribution of annual rate given as fractions e.g. 0.05 for 5%
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distribution {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl Distribution {
+    /// Mean and sample standard deviation of annual rates
+    fn of_rates(rates: &[f64]) -> Option<Distribution> {
+        if rates.len() < 2 {
+            return None;
+        }
+        let n = rates.len() as f64;
+        let mean = rates.iter().sum::<f64>() / n;
+        let variance = rates.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        Some(Distribution {
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+
+    /// Mean and sample standard deviation of annual dividend growth estimated from dividend
+    /// history. First and last year of history are skipped as they may be partial
+    pub fn dividend_growth(div_history: &[(String, f64)]) -> Option<Distribution> {
+        let rates: Vec<f64> = annual_dividends(div_history)
+            .windows(2)
+            .filter(|w| w[0].1 > 0.0)
+            .map(|w| w[1].1 / w[0].1 - 1.0)
+            .collect();
+        Distribution::of_rates(&rates)
+    }
+
+    /// Mean and sample standard deviation of annual share price growth estimated from closes
+    /// at the end of consecutive years of price history
+    pub fn price_growth(prices: &PriceHistory, symbol: &str) -> Option<Distribution> {
+        let rates: Vec<f64> = prices
+            .year_end_closes(symbol)
+            .windows(2)
+            .filter(|w| w[1].0 == w[0].0 + 1 && w[0].1 > 0.0)
+            .map(|w| w[1].1 / w[0].1 - 1.0)
+            .collect();
+        Distribution::of_rates(&rates)
+    }
+}
+
+/// Parameters of Monte Carlo simulation of dividend forecast
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonteCarloParams {
+    /// Annual share price growth
+    pub price_growth: Distribution,
+    /// Annual dividend growth
+    pub div_growth: Distribution,
+    /// Means of (share price growth, dividend growth) of consecutive years e.g. rates of
+    /// dividend growth model. Years not covered use means of distributions
+    pub annual_means: Vec<(f64, f64)>,
+    /// Correlation between share price growth and dividend growth of the same year
+    pub correlation: f64,
+    pub num_paths: usize,
+    /// Seed of random generator of first path, following paths use consecutive seeds so
+    /// results are reproducible regardless of number of threads
+    pub seed: u64,
+}
+
+impl MonteCarloParams {
+    /// Correlated (share price growth, dividend growth) of consecutive years. Share price
+    /// cannot drop below 1% of its value and dividends cannot become negative
+    fn draw_rates<R: Rng>(&self, rng: &mut R, num_years: usize) -> Vec<(f64, f64)> {
+        let correlation = self.correlation.clamp(-1.0, 1.0);
+        (0..num_years)
+            .map(|year| {
+                let (price_mean, div_mean) = self
+                    .annual_means
+                    .get(year)
+                    .copied()
+                    .unwrap_or((self.price_growth.mean, self.div_growth.mean));
+                let z1: f64 = rng.sample(StandardNormal);
+                let z2: f64 = rng.sample(StandardNormal);
+                let z_div = correlation * z1 + (1.0 - correlation * correlation).sqrt() * z2;
+                (
+                    (price_mean + self.price_growth.std_dev * z1).max(-0.99),
+                    (div_mean + self.div_growth.std_dev * z_div).max(-1.0),
+                )
+            })
+            .collect()
+    }
+}
+
+/// 5th, 25th, 50th (median), 75th and 95th percentile of simulated paths at sampled days
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bands {
+    pub p5: Vec<f64>,
+    pub p25: Vec<f64>,
+    pub p50: Vec<f64>,
+    pub p75: Vec<f64>,
+    pub p95: Vec<f64>,
+}
+
+/// Percentile (0-100) of sorted values, linearly interpolated between closest ranks
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+impl Bands {
+    fn from_paths(paths: &[Vec<f64>], num_samples: usize) -> Self {
+        let mut bands = Bands::default();
+        (0..num_samples).for_each(|i| {
+            let mut values: Vec<f64> = paths.iter().map(|path| path[i]).collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            bands.p5.push(percentile(&values, 5.0));
+            bands.p25.push(percentile(&values, 25.0));
+            bands.p50.push(percentile(&values, 50.0));
+            bands.p75.push(percentile(&values, 75.0));
+            bands.p95.push(percentile(&values, 95.0));
+        });
+        bands
+    }
+}
+
+/// Result of Monte Carlo simulation of dividend forecast
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MonteCarloForecast {
+    /// Days of time line bands are given for
+    pub days: Vec<u32>,
+    /// Total dividends received (after tax)
+    pub gains: Bands,
+    /// Value of shares held and cash waiting to be reinvested
+    pub value: Bands,
+    /// Final annual dividend income (after tax) of every path, sorted
+    pub final_income: Vec<f64>,
+}
+
+impl MonteCarloForecast {
+    /// Fraction of paths whose final annual dividend income reached given target
+    pub fn probability_of_income(&self, target_income: f64) -> f64 {
+        if self.final_income.is_empty() {
+            return 0.0;
+        }
+        let reached = self
+            .final_income
+            .iter()
+            .filter(|income| **income >= target_income)
+            .count();
+        reached as f64 / self.final_income.len() as f64
+    }
+}
+
+/// Forecast dividends over time line [days] along many paths of randomly drawn annual share
+/// price growth and dividend growth. Paths are simulated in parallel
+pub fn simulate(
+    params: &ForecastParams,
+    time_line: &[u32],
+    monte_carlo: &MonteCarloParams,
+) -> MonteCarloForecast {
+    let num_years = time_line.last().map_or(0, |day| day / 365) as usize;
+    let samples: Vec<usize> = (0..time_line.len())
+        .filter(|i| (i + 1) % SAMPLING_PERIOD == 0 || i + 1 == time_line.len())
+        .collect();
+
+    let paths: Vec<(Vec<f64>, Vec<f64>, f64)> = (0..monte_carlo.num_paths)
+        .into_par_iter()
+        .map(|path| {
+            let mut rng = StdRng::seed_from_u64(monte_carlo.seed.wrapping_add(path as u64));
+            let rates = monte_carlo.draw_rates(&mut rng, num_years);
+            let forecast = forecast_dividends_with_rates(params, time_line, &rates);
+            (
+                samples.iter().map(|i| forecast.gains[*i]).collect(),
+                samples.iter().map(|i| forecast.value[*i]).collect(),
+                forecast.income.last().copied().unwrap_or(0.0),
+            )
+        })
+        .collect();
+
+    let gains: Vec<Vec<f64>> = paths.iter().map(|(gains, _, _)| gains.clone()).collect();
+    let value: Vec<Vec<f64>> = paths.iter().map(|(_, value, _)| value.clone()).collect();
+    let mut final_income: Vec<f64> = paths.iter().map(|(_, _, income)| *income).collect();
+    final_income.sort_by(|a, b| a.total_cmp(b));
+
+    MonteCarloForecast {
+        days: samples.iter().map(|i| time_line[*i]).collect(),
+        gains: Bands::from_paths(&gains, samples.len()),
+        value: Bands::from_paths(&value, samples.len()),
+        final_income,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::{forecast_dividends, Reinvestment};
+
+    fn params() -> ForecastParams {
+        ForecastParams {
+            base_capital: 1000.0,
+            div_yield: 0.05,
+            div_growth: 0.05,
+            share_price: 100.0,
+            share_price_growth_rate: 0.07,
+            tax_rate: 0.15,
+            num_capitalizations: 4,
+            reinvestment: Reinvestment::Fractional,
+            contributions: None,
+        }
+    }
+
+    fn monte_carlo(std_dev: f64) -> MonteCarloParams {
+        MonteCarloParams {
+            price_growth: Distribution {
+                mean: 0.07,
+                std_dev,
+            },
+            div_growth: Distribution {
+                mean: 0.05,
+                std_dev,
+            },
+            annual_means: vec![],
+            correlation: 0.5,
+            num_paths: 200,
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn test_percentile() -> Result<(), String> {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&values, 50.0), 3.0);
+        assert_eq!(percentile(&values, 25.0), 2.0);
+        assert_eq!(percentile(&values, 10.0), 1.4);
+        assert_eq!(percentile(&[], 50.0), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dividend_growth() -> Result<(), String> {
+        let div_history: Vec<(String, f64)> = vec![
+            ("2019-12-01", 0.5),
+            ("2020-06-01", 0.5),
+            ("2020-12-01", 0.5),
+            ("2021-06-01", 0.55),
+            ("2021-12-01", 0.55),
+            ("2022-06-01", 0.66),
+            ("2022-12-01", 0.66),
+            ("2023-06-01", 0.7),
+        ]
+        .into_iter()
+        .map(|(d, a)| (d.to_owned(), a))
+        .collect();
+        // Full years 2020-2022: growth of 10% and 20%
+        let distribution = Distribution::dividend_growth(&div_history).ok_or("No distribution")?;
+        assert_eq!((distribution.mean * 1000.0).round() / 1000.0, 0.15);
+        assert_eq!((distribution.std_dev * 1000.0).round() / 1000.0, 0.071);
+        assert_eq!(Distribution::dividend_growth(&div_history[..4]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_price_growth() -> Result<(), String> {
+        let date = |d: &str| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let prices = PriceHistory::from_closes(&[
+            ("ABM", date("2019-06-03"), 90.0),
+            ("ABM", date("2019-12-31"), 100.0),
+            ("ABM", date("2020-12-31"), 110.0),
+            ("ABM", date("2021-06-01"), 120.0),
+            ("ABM", date("2021-12-30"), 132.0),
+            ("ABM", date("2022-03-01"), 150.0),
+        ]);
+        // Year-end closes of 2019-2021 (2022 is partial): growth of 10% and 20%
+        let distribution = Distribution::price_growth(&prices, "ABM").ok_or("No distribution")?;
+        assert_eq!((distribution.mean * 1000.0).round() / 1000.0, 0.15);
+        assert_eq!((distribution.std_dev * 1000.0).round() / 1000.0, 0.071);
+        assert_eq!(Distribution::price_growth(&prices, "XOM"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_simulate() -> Result<(), String> {
+        let time_line: Vec<u32> = (1..365 * 5 + 1).collect();
+
+        // With no volatility every path is deterministic forecast
+        let forecast = forecast_dividends(&params(), &time_line);
+        let simulation = simulate(&params(), &time_line, &monte_carlo(0.0));
+        assert_eq!(simulation.days.last(), time_line.last());
+        let final_gain = *simulation.gains.p50.last().unwrap();
+        assert!((final_gain - forecast.gains.last().unwrap()).abs() < 1e-9);
+        assert!((simulation.gains.p5.last().unwrap() - final_gain).abs() < 1e-9);
+        let income = *forecast.income.last().unwrap();
+        assert_eq!(simulation.probability_of_income(income - 1.0), 1.0);
+        assert_eq!(simulation.probability_of_income(income + 1.0), 0.0);
+
+        // Bands are ordered and simulation is reproducible
+        let simulation = simulate(&params(), &time_line, &monte_carlo(0.1));
+        let value = &simulation.value;
+        let last = value.p50.len() - 1;
+        assert!(value.p5[last] <= value.p25[last] && value.p25[last] <= value.p50[last]);
+        assert!(value.p50[last] <= value.p75[last] && value.p75[last] <= value.p95[last]);
+        assert!(value.p5[last] < value.p95[last]);
+        assert_eq!(
+            simulation,
+            simulate(&params(), &time_line, &monte_carlo(0.1))
+        );
+
+        // Paths are centered on rates of consecutive years
+        let annual_means = vec![(0.1, 0.2), (0.0, 0.1), (-0.05, 0.0)];
+        let forecast = forecast_dividends_with_rates(&params(), &time_line, &annual_means);
+        let simulation = simulate(
+            &params(),
+            &time_line,
+            &MonteCarloParams {
+                annual_means,
+                ..monte_carlo(0.0)
+            },
+        );
+        let final_gain = *simulation.gains.p50.last().unwrap();
+        assert!((final_gain - forecast.gains.last().unwrap()).abs() < 1e-9);
+        Ok(())
+    }
+}
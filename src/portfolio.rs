@@ -0,0 +1,447 @@
+use crate::forecast::{Forecast, ForecastParams, Position, Reinvestment};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How capital is split across holdings of portfolio
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Allocation {
+    /// Every holding gets the same part of capital
+    #[default]
+    Equal,
+    /// Holdings get capital proportional to their weights
+    Weights(BTreeMap<String, f64>),
+    /// Holdings are given as number of shares held, capital is not used
+    Shares(BTreeMap<String, f64>),
+}
+
+/// Parse "SYMBOL=VALUE" specifications
+fn parse_specs(specs: &[String]) -> Result<BTreeMap<String, f64>, &'static str> {
+    specs
+        .iter()
+        .map(|spec| {
+            let (symbol, value) = spec
+                .split_once('=')
+                .ok_or("Error: Allocation should be given as SYMBOL=VALUE")?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| "Error: Allocation value is not a number")?;
+            if value < 0.0 {
+                return Err("Error: Allocation value cannot be negative");
+            }
+            Ok((symbol.trim().to_owned(), value))
+        })
+        .collect()
+}
+
+impl Allocation {
+    /// Allocation of "SYMBOL=WEIGHT" or "SYMBOL=SHARES" specifications. Equal weighting is used
+    /// when neither is given
+    pub fn from_specs(weights: &[String], shares: &[String]) -> Result<Allocation, &'static str> {
+        match (weights.is_empty(), shares.is_empty()) {
+            (true, true) => Ok(Allocation::Equal),
+            (false, true) => {
+                let weights = parse_specs(weights)?;
+                if weights.values().sum::<f64>() <= 0.0 {
+                    return Err("Error: Sum of allocation weights should be positive");
+                }
+                Ok(Allocation::Weights(weights))
+            }
+            (true, false) => Ok(Allocation::Shares(parse_specs(shares)?)),
+            (false, false) => Err("Error: Allocation can be given either by weights or shares"),
+        }
+    }
+
+    /// Capital invested in a holding of portfolio of given number of holdings
+    pub fn capital_of(
+        &self,
+        name: &str,
+        capital: f64,
+        num_holdings: usize,
+        share_price: f64,
+    ) -> Result<f64, &'static str> {
+        let missing = || {
+            log::error!("No allocation given for {name}");
+            "Error: Every holding of portfolio needs allocation"
+        };
+        match self {
+            Allocation::Equal => Ok(capital / num_holdings.max(1) as f64),
+            Allocation::Weights(weights) => {
+                let weight = weights.get(name).ok_or_else(missing)?;
+                Ok(capital * weight / weights.values().sum::<f64>())
+            }
+            Allocation::Shares(shares) => Ok(shares.get(name).ok_or_else(missing)? * share_price),
+        }
+    }
+}
+
+/// Forecast of all holdings of portfolio, values are given after every day of time line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortfolioForecast {
+    /// Total dividends received (after tax)
+    pub gains: Vec<f64>,
+    /// Value of shares held and cash waiting to be reinvested
+    pub value: Vec<f64>,
+    /// Annual dividend income (after tax) of shares held
+    pub income: Vec<f64>,
+    /// Total capital invested
+    pub cost_basis: Vec<f64>,
+    /// Share of every holding in final annual income[%]
+    pub income_contributions: Vec<(String, f64)>,
+    /// Share of every holding in final value[%]
+    pub value_contributions: Vec<(String, f64)>,
+}
+
+fn sum_series<'a>(series: impl Iterator<Item = &'a Vec<f64>>) -> Vec<f64> {
+    series.fold(vec![], |mut total, values| {
+        total.resize(total.len().max(values.len()), 0.0);
+        total.iter_mut().zip(values).for_each(|(t, v)| *t += v);
+        total
+    })
+}
+
+fn contributions(
+    holdings: &[(String, Forecast)],
+    total: f64,
+    of: fn(&Forecast) -> f64,
+) -> Vec<(String, f64)> {
+    holdings
+        .iter()
+        .map(|(name, forecast)| {
+            let share = if total > 0.0 {
+                of(forecast) / total * 100.0
+            } else {
+                0.0
+            };
+            (name.clone(), share)
+        })
+        .collect()
+}
+
+/// Portfolio forecast as sum of forecasts of its holdings
+pub fn aggregate(holdings: &[(String, Forecast)]) -> PortfolioForecast {
+    let gains = sum_series(holdings.iter().map(|(_, f)| &f.gains));
+    let value = sum_series(holdings.iter().map(|(_, f)| &f.value));
+    let income = sum_series(holdings.iter().map(|(_, f)| &f.income));
+    let cost_basis = sum_series(holdings.iter().map(|(_, f)| &f.cost_basis));
+    let final_income = *income.last().unwrap_or(&0.0);
+    let final_value = *value.last().unwrap_or(&0.0);
+    PortfolioForecast {
+        income_contributions: contributions(holdings, final_income, |f| {
+            *f.income.last().unwrap_or(&0.0)
+        }),
+        value_contributions: contributions(holdings, final_value, |f| {
+            *f.value.last().unwrap_or(&0.0)
+        }),
+        gains,
+        value,
+        income,
+        cost_basis,
+    }
+}
+
+/// Where dividends received by portfolio holdings go
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Strategy {
+    /// Dividends buy shares of the company which paid them
+    #[default]
+    ReinvestInPayer,
+    /// All dividends buy shares of the holding of highest current Div Yield
+    HighestYield,
+    /// All dividends buy shares of the holding most below its target weight
+    MostUnderweight,
+    /// Dividends are reinvested in payer and holdings are brought back to target weights every
+    /// given number of years
+    Rebalance { period_years: u32 },
+    /// Dividends are collected as cash
+    CollectCash,
+}
+
+impl Strategy {
+    /// Strategy of name: "payer", "highest-yield", "underweight", "rebalance[:<years>]", "cash"
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        match name.trim().to_lowercase().as_str() {
+            "payer" | "reinvest" => Some(Strategy::ReinvestInPayer),
+            "highest-yield" => Some(Strategy::HighestYield),
+            "underweight" => Some(Strategy::MostUnderweight),
+            "rebalance" => Some(Strategy::Rebalance { period_years: 1 }),
+            "cash" => Some(Strategy::CollectCash),
+            name => {
+                let years = name.strip_prefix("rebalance:")?.parse().ok()?;
+                (years > 0).then_some(Strategy::Rebalance {
+                    period_years: years,
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Strategy::ReinvestInPayer => write!(f, "reinvest into payer"),
+            Strategy::HighestYield => write!(f, "redirect to highest yield"),
+            Strategy::MostUnderweight => write!(f, "redirect to most underweight"),
+            Strategy::Rebalance { period_years } => {
+                write!(f, "rebalance every {period_years} years")
+            }
+            Strategy::CollectCash => write!(f, "collect cash"),
+        }
+    }
+}
+
+/// Holding of portfolio: its forecast params (base capital is capital invested in it) and
+/// (share price growth, dividend growth) of consecutive years
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub name: String,
+    pub params: ForecastParams,
+    pub annual_rates: Vec<(f64, f64)>,
+}
+
+/// Forecast of holdings held together over time line [days] with dividends directed by given
+/// strategy. Target weights are weights of capital initially invested in holdings. Dividends are
+/// counted in gains of the holding which paid them, regardless of where they are reinvested, and
+/// buy shares as reinvestment of the receiving holding does
+pub fn forecast_portfolio(
+    holdings: &[Holding],
+    time_line: &[u32],
+    strategy: Strategy,
+) -> Vec<(String, Forecast)> {
+    let mut positions: Vec<Position> = holdings.iter().map(|h| Position::new(&h.params)).collect();
+    let total_capital: f64 = holdings.iter().map(|h| h.params.base_capital).sum();
+    let targets: Vec<f64> = holdings
+        .iter()
+        .map(|h| {
+            if total_capital > 0.0 {
+                h.params.base_capital / total_capital
+            } else {
+                1.0 / holdings.len() as f64
+            }
+        })
+        .collect();
+    let mut forecasts: Vec<(String, Forecast)> = holdings
+        .iter()
+        .map(|h| (h.name.clone(), Forecast::default()))
+        .collect();
+
+    time_line.iter().for_each(|x| {
+        (0..holdings.len()).for_each(|i| {
+            let params = &holdings[i].params;
+            if let Some(g) = positions[i].pay_out(params, *x) {
+                forecasts[i].1.last_payout = g;
+                let target = match strategy {
+                    Strategy::ReinvestInPayer | Strategy::Rebalance { .. } => Some(i),
+                    Strategy::HighestYield => (0..positions.len()).max_by(|a, b| {
+                        let div_yield = |p: &Position| p.curr_div / p.share_price;
+                        div_yield(&positions[*a]).total_cmp(&div_yield(&positions[*b]))
+                    }),
+                    Strategy::MostUnderweight => {
+                        let total: f64 = positions.iter().map(|p| p.value()).sum();
+                        (0..positions.len()).min_by(|a, b| {
+                            let gap = |j: usize| positions[j].value() / total - targets[j];
+                            gap(*a).total_cmp(&gap(*b))
+                        })
+                    }
+                    Strategy::CollectCash => None,
+                };
+                // Dividends are bought as reinvestment of the receiving holding does, holdings
+                // without reinvestment collect them as cash of the payer
+                match target.filter(|j| holdings[*j].params.reinvestment != Reinvestment::None) {
+                    Some(j) => positions[j].buy(g, holdings[j].params.reinvestment),
+                    None => positions[i].cash += g,
+                }
+            }
+            positions[i].contribute(params, *x);
+        });
+        if x % 365 == 0 {
+            let year = (x / 365) as usize;
+            holdings
+                .iter()
+                .zip(&mut positions)
+                .for_each(|(h, position)| {
+                    let rates = h
+                        .annual_rates
+                        .get(year - 1)
+                        .copied()
+                        .unwrap_or((h.params.share_price_growth_rate, h.params.div_growth));
+                    position.grow(&h.params, rates);
+                });
+            if let Strategy::Rebalance { period_years } = strategy {
+                if year % period_years.max(1) as usize == 0 {
+                    let total: f64 = positions.iter().map(|p| p.value()).sum();
+                    positions.iter_mut().zip(holdings).zip(&targets).for_each(
+                        |((position, h), target)| {
+                            position.shares = 0.0;
+                            position.cash = 0.0;
+                            let reinvestment = match h.params.reinvestment {
+                                Reinvestment::WholeShares => Reinvestment::WholeShares,
+                                _ => Reinvestment::Fractional,
+                            };
+                            position.buy(total * target, reinvestment);
+                        },
+                    );
+                }
+            }
+        }
+        holdings
+            .iter()
+            .zip(&positions)
+            .zip(&mut forecasts)
+            .for_each(|((h, position), (_, forecast))| position.record(&h.params, forecast));
+    });
+
+    forecasts
+        .iter_mut()
+        .zip(&positions)
+        .for_each(|((_, forecast), position)| forecast.final_value = position.value());
+    forecasts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::{forecast_dividends, Contributions};
+
+    fn specs(specs: &[&str]) -> Vec<String> {
+        specs.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_allocation() -> Result<(), String> {
+        let equal = Allocation::from_specs(&[], &[])?;
+        assert_eq!(equal.capital_of("ABM", 9000.0, 3, 50.0)?, 3000.0);
+
+        let weights = Allocation::from_specs(&specs(&["ABM=3", "CTBI=1"]), &[])?;
+        assert_eq!(weights.capital_of("ABM", 10000.0, 2, 50.0)?, 7500.0);
+        assert_eq!(weights.capital_of("CTBI", 10000.0, 2, 50.0)?, 2500.0);
+        assert!(weights.capital_of("XOM", 10000.0, 2, 50.0).is_err());
+
+        let shares = Allocation::from_specs(&[], &specs(&["ABM=20"]))?;
+        assert_eq!(shares.capital_of("ABM", 10000.0, 1, 45.5)?, 910.0);
+
+        assert!(Allocation::from_specs(&specs(&["ABM"]), &[]).is_err());
+        assert!(Allocation::from_specs(&specs(&["ABM=1"]), &specs(&["ABM=1"])).is_err());
+        Ok(())
+    }
+
+    fn holding(name: &str, div_yield: f64, share_price_growth_rate: f64) -> Holding {
+        Holding {
+            name: name.to_owned(),
+            params: ForecastParams {
+                base_capital: 1000.0,
+                div_yield,
+                div_growth: 0.0,
+                share_price: 100.0,
+                share_price_growth_rate,
+                tax_rate: 0.0,
+                num_capitalizations: 4,
+                reinvestment: Reinvestment::Fractional,
+                contributions: None,
+            },
+            annual_rates: vec![],
+        }
+    }
+
+    #[test]
+    fn test_strategy_from_name() -> Result<(), String> {
+        assert_eq!(
+            Strategy::from_name("payer"),
+            Some(Strategy::ReinvestInPayer)
+        );
+        assert_eq!(
+            Strategy::from_name("rebalance:2"),
+            Some(Strategy::Rebalance { period_years: 2 })
+        );
+        assert_eq!(Strategy::from_name("rebalance:0"), None);
+        assert_eq!(Strategy::from_name("unknown"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_forecast_portfolio() -> Result<(), String> {
+        let time_line: Vec<u32> = (1..365 * 3 + 1).collect();
+        let holdings = vec![holding("ABM", 0.02, 0.1), holding("XOM", 0.08, 0.0)];
+
+        // Reinvesting into payer is forecast of every holding on its own
+        let forecasts = forecast_portfolio(&holdings, &time_line, Strategy::ReinvestInPayer);
+        let alone = forecast_dividends(&holdings[1].params, &time_line);
+        assert_eq!(forecasts[1].1.gains, alone.gains);
+        assert_eq!(forecasts[1].1.final_value, alone.final_value);
+
+        // All dividends go to XOM of highest yield
+        let forecasts = forecast_portfolio(&holdings, &time_line, Strategy::HighestYield);
+        assert_eq!(forecasts[0].1.shares.last(), Some(&10.0));
+        assert!(forecasts[1].1.shares.last() > alone.shares.last());
+
+        // Collected dividends are cash of the payer
+        let forecasts = forecast_portfolio(&holdings, &time_line, Strategy::CollectCash);
+        assert_eq!(forecasts[1].1.shares.last(), Some(&10.0));
+        let xom = &forecasts[1].1;
+        assert_eq!(xom.final_value, 1000.0 + xom.gains.last().unwrap());
+
+        // Rebalancing brings holdings back to equal weights at the end of every year
+        let forecasts = forecast_portfolio(
+            &holdings,
+            &time_line,
+            Strategy::Rebalance { period_years: 1 },
+        );
+        let (abm, xom) = (&forecasts[0].1, &forecasts[1].1);
+        assert!((abm.final_value - xom.final_value).abs() < 1e-9);
+
+        // Redirecting to most underweight keeps ABM (growing) from dominating portfolio
+        let underweight = forecast_portfolio(&holdings, &time_line, Strategy::MostUnderweight);
+        let payer = forecast_portfolio(&holdings, &time_line, Strategy::ReinvestInPayer);
+        assert!(underweight[1].1.shares.last() > payer[1].1.shares.last());
+
+        // Reinvesting whole shares into payer is forecast of every holding on its own
+        let holdings: Vec<Holding> = holdings
+            .into_iter()
+            .map(|mut h| {
+                h.params.reinvestment = Reinvestment::WholeShares;
+                h.params.contributions = Some(Contributions {
+                    amount: 150.0,
+                    per_year: 12,
+                    growth: 0.0,
+                });
+                h
+            })
+            .collect();
+        let forecasts = forecast_portfolio(&holdings, &time_line, Strategy::ReinvestInPayer);
+        holdings
+            .iter()
+            .zip(&forecasts)
+            .for_each(|(h, (_, forecast))| {
+                assert_eq!(*forecast, forecast_dividends(&h.params, &time_line));
+            });
+        assert!(forecasts[1].1.shares.iter().all(|s| s.fract() == 0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregate() -> Result<(), String> {
+        let forecast = |income: f64, value: f64| Forecast {
+            gains: vec![income / 4.0, income / 2.0],
+            value: vec![value, value],
+            income: vec![income, income],
+            cost_basis: vec![value, value],
+            ..Default::default()
+        };
+        let portfolio = aggregate(&[
+            ("ABM".to_owned(), forecast(30.0, 1000.0)),
+            ("CTBI".to_owned(), forecast(10.0, 3000.0)),
+        ]);
+        assert_eq!(portfolio.gains, vec![10.0, 20.0]);
+        assert_eq!(portfolio.income, vec![40.0, 40.0]);
+        assert_eq!(portfolio.value.last(), Some(&4000.0));
+        assert_eq!(
+            portfolio.income_contributions,
+            vec![("ABM".to_owned(), 75.0), ("CTBI".to_owned(), 25.0)]
+        );
+        assert_eq!(
+            portfolio.value_contributions,
+            vec![("ABM".to_owned(), 25.0), ("CTBI".to_owned(), 75.0)]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+/// Metrics that companies can be ranked by and whether higher value is better
+pub const RANKING_FACTORS: [(&str, &str, bool); 5] = [
+    ("yield", "Yield", true),
+    ("dgr", "DGR", true),
+    ("streak", "Streak", true),
+    ("payout", "Payout headroom", false),
+    ("valuation", "Valuation", false),
+];
+
+/// How metrics are made comparable with each other within ranked list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalization {
+    /// Percentile (0-100) of a value within the list
+    Percentile,
+    /// Number of standard deviations from mean of the list
+    ZScore,
+}
+
+impl Normalization {
+    pub fn from_name(name: &str) -> Option<Normalization> {
+        match name.trim().to_lowercase().as_str() {
+            "percentile" => Some(Normalization::Percentile),
+            "zscore" | "z-score" => Some(Normalization::ZScore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankingFactor {
+    /// Name of the factor as given on command line e.g. "yield"
+    pub name: &'static str,
+    /// Name of the factor as presented in ranking
+    pub title: &'static str,
+    pub weight: f64,
+    pub higher_is_better: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranking {
+    pub normalization: Normalization,
+    pub factors: Vec<RankingFactor>,
+    /// Number of best ranked companies to present
+    pub top: usize,
+}
+
+/// Score of a single company with contribution of each factor (None when there was no data)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rank {
+    pub score: f64,
+    pub contributions: Vec<Option<f64>>,
+}
+
+/// Parse weights given as "<factor>=<weight>" e.g. "yield=2". All factors are
+/// weighted equally when no weights are given
+pub fn parse_weights(weights: &[String]) -> Result<Vec<RankingFactor>, &'static str> {
+    let factor = |name: &str, weight: f64| {
+        RANKING_FACTORS
+            .iter()
+            .find(|(factor, _, _)| *factor == name)
+            .map(|&(name, title, higher_is_better)| RankingFactor {
+                name,
+                title,
+                weight,
+                higher_is_better,
+            })
+    };
+    if weights.is_empty() {
+        return Ok(RANKING_FACTORS
+            .iter()
+            .filter_map(|(name, _, _)| factor(*name, 1.0))
+            .collect());
+    }
+    weights
+        .iter()
+        .map(|weight| {
+            let (name, value) = weight.split_once('=').ok_or_else(|| {
+                log::error!("Invalid ranking weight: {weight}");
+                "Error: Ranking weight should be given as <factor>=<weight>"
+            })?;
+            let value = value.trim().parse::<f64>().map_err(|_| {
+                log::error!("Invalid ranking weight: {weight}");
+                "Error: Ranking weight is not a number"
+            })?;
+            factor(name.trim(), value).ok_or_else(|| {
+                log::error!("Unknown ranking factor: {name}");
+                "Error: Unknown ranking factor"
+            })
+        })
+        .collect()
+}
+
+/// Normalize values within the list so that higher normalized value is always better
+pub fn normalize(
+    values: &[Option<f64>],
+    normalization: Normalization,
+    higher_is_better: bool,
+) -> Vec<Option<f64>> {
+    let known: Vec<f64> = values.iter().flatten().copied().collect();
+    let n = known.len() as f64;
+    let direction = if higher_is_better { 1.0 } else { -1.0 };
+    match normalization {
+        Normalization::Percentile => values
+            .iter()
+            .map(|value| {
+                value.map(|v| {
+                    if known.len() < 2 {
+                        return 50.0;
+                    }
+                    let below = known
+                        .iter()
+                        .filter(|x| **x * direction < v * direction)
+                        .count();
+                    // Ties share the percentile between them
+                    let ties = known.iter().filter(|x| **x == v).count() - 1;
+                    (below as f64 + ties as f64 / 2.0) / (n - 1.0) * 100.0
+                })
+            })
+            .collect(),
+        Normalization::ZScore => {
+            let mean = known.iter().sum::<f64>() / n;
+            let std_dev = (known.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+            values
+                .iter()
+                .map(|value| {
+                    value.map(|v| {
+                        if std_dev > 0.0 {
+                            (v - mean) / std_dev * direction
+                        } else {
+                            0.0
+                        }
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+/// Rank companies given values of every factor (one vector per factor, one value per company).
+/// Score is weighted average of normalized values over factors with data available
+pub fn rank(ranking: &Ranking, values: &[Vec<Option<f64>>]) -> Vec<Rank> {
+    let normalized: Vec<Vec<Option<f64>>> = ranking
+        .factors
+        .iter()
+        .zip(values)
+        .map(|(factor, values)| normalize(values, ranking.normalization, factor.higher_is_better))
+        .collect();
+    let num_companies = values.first().map_or(0, |v| v.len());
+
+    (0..num_companies)
+        .map(|i| {
+            let total_weight: f64 = ranking
+                .factors
+                .iter()
+                .zip(&normalized)
+                .filter(|(_, values)| values[i].is_some())
+                .map(|(factor, _)| factor.weight)
+                .sum();
+            let contributions: Vec<Option<f64>> = ranking
+                .factors
+                .iter()
+                .zip(&normalized)
+                .map(|(factor, values)| {
+                    values[i].map(|v| {
+                        if total_weight > 0.0 {
+                            v * factor.weight / total_weight
+                        } else {
+                            0.0
+                        }
+                    })
+                })
+                .collect();
+            Rank {
+                score: contributions.iter().flatten().sum(),
+                contributions,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_weights() -> Result<(), String> {
+        let factors = parse_weights(&[])?;
+        assert_eq!(factors.len(), RANKING_FACTORS.len());
+
+        let factors = parse_weights(&["yield=2".to_owned(), "payout = 0.5".to_owned()])?;
+        assert_eq!(factors.len(), 2);
+        assert_eq!(factors[0].weight, 2.0);
+        assert_eq!(factors[1].weight, 0.5);
+        assert!(!factors[1].higher_is_better);
+
+        assert!(parse_weights(&["beta=1".to_owned()]).is_err());
+        assert!(parse_weights(&["yield".to_owned()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize() -> Result<(), String> {
+        let round2 = |values: Vec<Option<f64>>| -> Vec<Option<f64>> {
+            values
+                .into_iter()
+                .map(|v| v.map(|v| (v * 100.0).round() / 100.0))
+                .collect()
+        };
+        let values = vec![Some(1.0), Some(3.0), None, Some(2.0), Some(3.0)];
+        assert_eq!(
+            round2(normalize(&values, Normalization::Percentile, true)),
+            vec![Some(0.0), Some(83.33), None, Some(33.33), Some(83.33)]
+        );
+        assert_eq!(
+            round2(normalize(&values, Normalization::Percentile, false)),
+            vec![Some(100.0), Some(16.67), None, Some(66.67), Some(16.67)]
+        );
+
+        let values = vec![Some(2.0), Some(4.0), Some(6.0)];
+        let zscores: Vec<f64> = normalize(&values, Normalization::ZScore, false)
+            .into_iter()
+            .map(|z| (z.unwrap() * 1000.0).round() / 1000.0)
+            .collect();
+        assert_eq!(zscores, vec![1.225, 0.0, -1.225]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rank() -> Result<(), String> {
+        let ranking = Ranking {
+            normalization: Normalization::Percentile,
+            factors: parse_weights(&["yield=3".to_owned(), "payout=1".to_owned()])?,
+            top: 10,
+        };
+        let values = vec![
+            vec![Some(5.0), Some(3.0), Some(4.0)],
+            vec![Some(80.0), Some(40.0), None],
+        ];
+        let ranks = rank(&ranking, &values);
+        // First: yield is best (100 * 3/4), payout is worse (0 * 1/4)
+        assert_eq!(ranks[0].contributions, vec![Some(75.0), Some(0.0)]);
+        assert_eq!(ranks[0].score, 75.0);
+        // Second: yield is worst, payout is best
+        assert_eq!(ranks[1].score, 25.0);
+        // Third: no payout data, so yield is the only factor
+        assert_eq!(ranks[2].contributions, vec![Some(50.0), None]);
+        assert_eq!(ranks[2].score, 50.0);
+        Ok(())
+    }
+}
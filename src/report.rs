@@ -0,0 +1,256 @@
+use crate::export::cell_text;
+use chrono::{Datelike, NaiveDate};
+use polars::prelude::*;
+
+const COLORS: [&str; 6] = ["blue", "green", "navy", "#2e8b57", "#127cc1", "#76B900"];
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 360.0;
+const CHART_MARGIN: f64 = 50.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartLine {
+    pub caption: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// Line chart rendered as inline SVG, so report can be opened offline
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub lines: Vec<ChartLine>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Section {
+    Heading(String),
+    Paragraph(String),
+    Table(String, DataFrame),
+    Chart(Chart),
+}
+
+/// Self-contained HTML report
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub title: String,
+    pub sections: Vec<Section>,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Date e.g. "2023-06-15" as fractional year e.g. 2023.45
+pub fn date_to_year(date: &str) -> Option<f64> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.year() as f64 + (date.ordinal0() as f64) / 365.0)
+}
+
+fn html_table(df: &DataFrame) -> String {
+    let header: String = df
+        .get_column_names()
+        .iter()
+        .map(|name| format!("<th>{}</th>", escape(name)))
+        .collect();
+    let rows: String = (0..df.height())
+        .map(|i| {
+            let cells: String = df
+                .get_columns()
+                .iter()
+                .map(|c| {
+                    let text = c.get(i).map(|v| cell_text(&v)).unwrap_or_default();
+                    let class = match text.split(' ').next() {
+                        Some("PASS") | Some("true") => " class=\"pass\"",
+                        Some("FAIL") | Some("false") => " class=\"fail\"",
+                        Some("NEAR") => " class=\"near\"",
+                        _ => "",
+                    };
+                    format!("<td{class}>{}</td>", escape(&text))
+                })
+                .collect();
+            format!("<tr>{cells}</tr>\n")
+        })
+        .collect();
+    format!("<table>\n<tr>{header}</tr>\n{rows}</table>\n")
+}
+
+impl Chart {
+    pub fn to_svg(&self) -> String {
+        let points = self.lines.iter().flat_map(|l| l.points.iter());
+        let (min_x, max_x, min_y, max_y) = points.fold(
+            (f64::MAX, f64::MIN, 0.0_f64, f64::MIN),
+            |(min_x, max_x, min_y, max_y), (x, y)| {
+                (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y))
+            },
+        );
+        if min_x > max_x {
+            return "<p>No data to plot</p>\n".to_owned();
+        }
+        // Flat data still needs a range to be plotted
+        let range_x = if max_x > min_x { max_x - min_x } else { 1.0 };
+        let range_y = if max_y > min_y { max_y - min_y } else { 1.0 };
+        let plot_width = CHART_WIDTH - 2.0 * CHART_MARGIN;
+        let plot_height = CHART_HEIGHT - 2.0 * CHART_MARGIN;
+        let to_svg_x = |x: f64| CHART_MARGIN + (x - min_x) / range_x * plot_width;
+        let to_svg_y = |y: f64| CHART_HEIGHT - CHART_MARGIN - (y - min_y) / range_y * plot_height;
+
+        let lines: String = self
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let color = COLORS[i % COLORS.len()];
+                let path: Vec<String> = line
+                    .points
+                    .iter()
+                    .map(|(x, y)| format!("{:.1},{:.1}", to_svg_x(*x), to_svg_y(*y)))
+                    .collect();
+                format!(
+                    "<polyline fill=\"none\" stroke=\"{color}\" stroke-width=\"2\" points=\"{}\"/>\n\
+                     <text x=\"{}\" y=\"{}\" fill=\"{color}\" font-size=\"12\">{}</text>\n",
+                    path.join(" "),
+                    CHART_MARGIN + 10.0,
+                    CHART_MARGIN + 15.0 * (i as f64 + 1.0),
+                    escape(&line.caption)
+                )
+            })
+            .collect();
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\">\n\
+             <text x=\"{}\" y=\"20\" text-anchor=\"middle\" font-size=\"15\">{}</text>\n\
+             <line x1=\"{CHART_MARGIN}\" y1=\"{y0}\" x2=\"{x1}\" y2=\"{y0}\" stroke=\"black\"/>\n\
+             <line x1=\"{CHART_MARGIN}\" y1=\"{CHART_MARGIN}\" x2=\"{CHART_MARGIN}\" y2=\"{y0}\" stroke=\"black\"/>\n\
+             <text x=\"{CHART_MARGIN}\" y=\"{}\" font-size=\"11\">{min_x:.2}</text>\n\
+             <text x=\"{x1}\" y=\"{}\" text-anchor=\"end\" font-size=\"11\">{max_x:.2}</text>\n\
+             <text x=\"{}\" y=\"{y0}\" text-anchor=\"end\" font-size=\"11\">{min_y:.2}</text>\n\
+             <text x=\"{}\" y=\"{CHART_MARGIN}\" text-anchor=\"end\" font-size=\"11\">{max_y:.2}</text>\n\
+             <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\">{}</text>\n\
+             <text x=\"12\" y=\"{}\" font-size=\"12\" transform=\"rotate(-90 12 {})\" text-anchor=\"middle\">{}</text>\n\
+             {lines}</svg>\n",
+            CHART_WIDTH / 2.0,
+            escape(&self.title),
+            CHART_HEIGHT - CHART_MARGIN + 15.0,
+            CHART_HEIGHT - CHART_MARGIN + 15.0,
+            CHART_MARGIN - 5.0,
+            CHART_MARGIN - 5.0,
+            CHART_WIDTH / 2.0,
+            CHART_HEIGHT - 10.0,
+            escape(&self.x_label),
+            CHART_HEIGHT / 2.0,
+            CHART_HEIGHT / 2.0,
+            escape(&self.y_label),
+            y0 = CHART_HEIGHT - CHART_MARGIN,
+            x1 = CHART_WIDTH - CHART_MARGIN,
+        )
+    }
+}
+
+impl Report {
+    pub fn new(title: &str) -> Self {
+        Report {
+            title: title.to_owned(),
+            sections: vec![],
+        }
+    }
+
+    pub fn push(&mut self, section: Section) {
+        self.sections.push(section);
+    }
+
+    pub fn to_html(&self) -> String {
+        let body: String = self
+            .sections
+            .iter()
+            .map(|section| match section {
+                Section::Heading(text) => format!("<h2>{}</h2>\n", escape(text)),
+                Section::Paragraph(text) => format!("<p>{}</p>\n", escape(text)),
+                Section::Table(caption, df) => {
+                    format!("<h3>{}</h3>\n{}", escape(caption), html_table(df))
+                }
+                Section::Chart(chart) => chart.to_svg(),
+            })
+            .collect();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>\n\
+             body {{ font-family: Arial, sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+             th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }}\n\
+             th {{ background: #eee; }}\n\
+             td.pass {{ background: #d4f4d4; }}\n\
+             td.fail {{ background: #f8d0d0; }}\n\
+             td.near {{ background: #fbeec1; }}\n\
+             </style>\n</head>\n<body>\n<h1>{title}</h1>\n{body}</body>\n</html>\n",
+            title = escape(&self.title)
+        )
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), &'static str> {
+        std::fs::write(path, self.to_html()).map_err(|e| {
+            log::error!("Unable to write {path}: {e}");
+            "Error: Unable to write report"
+        })?;
+        log::info!("Report written to {path}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_to_year() -> Result<(), String> {
+        assert_eq!(date_to_year("2023-01-01"), Some(2023.0));
+        assert_eq!(
+            date_to_year("2023-07-02").map(|y| (y * 100.0).round()),
+            Some(202350.0)
+        );
+        assert_eq!(date_to_year("N/A"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_to_html() -> Result<(), String> {
+        let s1 = Series::new("Symbol", &["ABM", "<INTC>"]);
+        let s2 = Series::new("Min Div Yield", &["PASS 5.54", "FAIL 1.32"]);
+        let df: DataFrame = DataFrame::new(vec![s1, s2]).unwrap();
+
+        let mut report = Report::new("Dividend report");
+        report.push(Section::Table("Criteria evaluation".to_owned(), df));
+        report.push(Section::Chart(Chart {
+            title: "ABM dividend history".to_owned(),
+            x_label: "Year".to_owned(),
+            y_label: "Dividend[$]".to_owned(),
+            lines: vec![ChartLine {
+                caption: "ABM".to_owned(),
+                points: vec![(2022.0, 0.195), (2023.0, 0.22)],
+            }],
+        }));
+        let html = report.to_html();
+        assert!(html.contains("<title>Dividend report</title>"));
+        assert!(html.contains("<td class=\"pass\">PASS 5.54</td>"));
+        assert!(html.contains("<td>&lt;INTC&gt;</td>"));
+        assert!(html.contains("<polyline"));
+        // No external resources so report can be opened offline
+        assert!(!html.contains("src="));
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_chart() -> Result<(), String> {
+        let chart = Chart {
+            title: "Empty".to_owned(),
+            x_label: "".to_owned(),
+            y_label: "".to_owned(),
+            lines: vec![],
+        };
+        assert_eq!(chart.to_svg(), "<p>No data to plot</p>\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use crate::growth::trend;
+
+/// Data that dividend safety score is built from. Missing data is skipped
+/// and the score is computed out of factors that are available
+#[derive(Debug, Clone, Default)]
+pub struct SafetyInputs {
+    /// Most recent dividend payout ratio[%]
+    pub payout_ratio: Option<f64>,
+    /// Payout ratios[%] of consecutive fiscal years, from older to newer
+    pub payout_history: Vec<f64>,
+    /// Number of years with annual dividend lower than in preceding year
+    pub dividend_cuts: Option<u32>,
+    pub years_of_growth: Option<u32>,
+    pub debt_to_equity: Option<f64>,
+    /// Operating cash flows of consecutive fiscal years, from older to newer
+    pub operating_cash_flows: Vec<f64>,
+    /// Dividend Yield[%]
+    pub div_yield: Option<f64>,
+}
+
+/// Contribution of a single factor to dividend safety score
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetyFactor {
+    pub name: &'static str,
+    /// Points granted (None when there was no data to judge this factor)
+    pub points: Option<f64>,
+    pub max_points: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetyScore {
+    /// Score in a range 0-100
+    pub score: f64,
+    pub grade: char,
+    pub factors: Vec<SafetyFactor>,
+}
+
+pub const SAFETY_FACTORS: [&str; 7] = [
+    "Payout level",
+    "Payout trend",
+    "Cut history",
+    "Streak",
+    "Debt level",
+    "Cash flow stability",
+    "Yield extremity",
+];
+
+/// Linear scaling of value into points: `best` (or better) gets max_points,
+/// `worst` (or worse) gets zero points
+fn scale(value: f64, best: f64, worst: f64, max_points: f64) -> f64 {
+    let ratio = (value - worst) / (best - worst);
+    ratio.clamp(0.0, 1.0) * max_points
+}
+
+fn payout_level_points(inputs: &SafetyInputs) -> Option<f64> {
+    // Up to 40% is safe and above 100% dividend is not covered by cash flow
+    inputs
+        .payout_ratio
+        .map(|payout| scale(payout, 40.0, 100.0, 25.0))
+}
+
+fn payout_trend_points(inputs: &SafetyInputs) -> Option<f64> {
+    // Falling payout is fine, growing by 10 percentage points a year is alarming
+    trend(&inputs.payout_history).map(|slope| scale(slope, 0.0, 10.0, 10.0))
+}
+
+fn cut_history_points(inputs: &SafetyInputs) -> Option<f64> {
+    inputs
+        .dividend_cuts
+        .map(|cuts| scale(cuts as f64, 0.0, 2.0, 15.0))
+}
+
+fn streak_points(inputs: &SafetyInputs) -> Option<f64> {
+    inputs
+        .years_of_growth
+        .map(|years| scale(years as f64, 25.0, 0.0, 15.0))
+}
+
+fn debt_level_points(inputs: &SafetyInputs) -> Option<f64> {
+    inputs.debt_to_equity.map(|dte| scale(dte, 0.5, 2.0, 15.0))
+}
+
+fn cash_flow_stability_points(inputs: &SafetyInputs) -> Option<f64> {
+    let cash_flows = &inputs.operating_cash_flows;
+    if cash_flows.len() < 2 {
+        return None;
+    }
+    // Negative operating cash flow in any year means dividend was paid out of debt or savings
+    if cash_flows.iter().any(|cf| *cf <= 0.0) {
+        return Some(0.0);
+    }
+    let n = cash_flows.len() as f64;
+    let mean = cash_flows.iter().sum::<f64>() / n;
+    let variance = cash_flows.iter().map(|cf| (cf - mean).powi(2)).sum::<f64>() / n;
+    let coefficient_of_variation = variance.sqrt() / mean;
+    Some(scale(coefficient_of_variation, 0.1, 0.5, 10.0))
+}
+
+fn yield_extremity_points(inputs: &SafetyInputs) -> Option<f64> {
+    // Yield above 7% is often a market expectation of a cut
+    inputs.div_yield.map(|divy| scale(divy, 7.0, 12.0, 10.0))
+}
+
+fn grade(score: f64) -> char {
+    match score {
+        s if s >= 80.0 => 'A',
+        s if s >= 65.0 => 'B',
+        s if s >= 50.0 => 'C',
+        s if s >= 35.0 => 'D',
+        _ => 'F',
+    }
+}
+
+/// Compute dividend safety score (0-100 and a letter grade) with contribution of each factor
+pub fn calculate_safety_score(inputs: &SafetyInputs) -> SafetyScore {
+    let factors: Vec<SafetyFactor> = vec![
+        (payout_level_points(inputs), 25.0),
+        (payout_trend_points(inputs), 10.0),
+        (cut_history_points(inputs), 15.0),
+        (streak_points(inputs), 15.0),
+        (debt_level_points(inputs), 15.0),
+        (cash_flow_stability_points(inputs), 10.0),
+        (yield_extremity_points(inputs), 10.0),
+    ]
+    .into_iter()
+    .zip(SAFETY_FACTORS)
+    .map(|((points, max_points), name)| SafetyFactor {
+        name,
+        points,
+        max_points,
+    })
+    .collect();
+
+    let (points, max_points) = factors
+        .iter()
+        .filter_map(|f| f.points.map(|p| (p, f.max_points)))
+        .fold((0.0, 0.0), |(acc_p, acc_m), (p, m)| (acc_p + p, acc_m + m));
+
+    let score = if max_points > 0.0 {
+        points / max_points * 100.0
+    } else {
+        log::warn!("No data to compute dividend safety score");
+        0.0
+    };
+    log::info!("Dividend safety score: {score:.2}, factors: {factors:?}");
+
+    SafetyScore {
+        score,
+        grade: grade(score),
+        factors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round2(val: f64) -> f64 {
+        (val * 100.0).round() / 100.0
+    }
+
+    #[test]
+    fn test_safety_score_full_data() -> Result<(), String> {
+        let inputs = SafetyInputs {
+            payout_ratio: Some(70.0),
+            payout_history: vec![50.0, 55.0, 60.0],
+            dividend_cuts: Some(1),
+            years_of_growth: Some(10),
+            debt_to_equity: Some(1.25),
+            operating_cash_flows: vec![100.0, 100.0, 100.0],
+            div_yield: Some(9.5),
+        };
+        let score = calculate_safety_score(&inputs);
+
+        // Payout level: (70-100)/(40-100)*25 = 12.5
+        // Payout trend: (5-10)/(0-10)*10 = 5.0
+        // Cut history: (1-2)/(0-2)*15 = 7.5
+        // Streak: 10/25*15 = 6.0
+        // Debt level: (1.25-2.0)/(0.5-2.0)*15 = 7.5
+        // Cash flow stability: 10.0
+        // Yield extremity: (9.5-12)/(7-12)*10 = 5.0
+        let points: Vec<Option<f64>> = score.factors.iter().map(|f| f.points.map(round2)).collect();
+        assert_eq!(
+            points,
+            vec![
+                Some(12.5),
+                Some(5.0),
+                Some(7.5),
+                Some(6.0),
+                Some(7.5),
+                Some(10.0),
+                Some(5.0)
+            ]
+        );
+        assert_eq!(round2(score.score), 53.5);
+        assert_eq!(score.grade, 'C');
+        Ok(())
+    }
+
+    #[test]
+    fn test_safety_score_missing_data() -> Result<(), String> {
+        // Only payout ratio and streak are known, so the score is out of 40 points
+        let inputs = SafetyInputs {
+            payout_ratio: Some(40.0),
+            years_of_growth: Some(25),
+            ..Default::default()
+        };
+        let score = calculate_safety_score(&inputs);
+        assert_eq!(score.score, 100.0);
+        assert_eq!(score.grade, 'A');
+        assert_eq!(score.factors[1].points, None);
+
+        let score = calculate_safety_score(&SafetyInputs::default());
+        assert_eq!(score.score, 0.0);
+        assert_eq!(score.grade, 'F');
+        Ok(())
+    }
+
+    #[test]
+    fn test_safety_score_negative_cash_flow() -> Result<(), String> {
+        let inputs = SafetyInputs {
+            operating_cash_flows: vec![100.0, -20.0, 120.0],
+            ..Default::default()
+        };
+        let score = calculate_safety_score(&inputs);
+        assert_eq!(score.factors[5].points, Some(0.0));
+        Ok(())
+    }
+}
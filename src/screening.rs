@@ -0,0 +1,570 @@
+use polars::prelude::*;
+use serde::Deserialize;
+
+/// Screens shipped with the project, selected by name
+pub const PRESETS: [(&str, &str); 4] = [
+    ("10-11-12", include_str!("../screens/10-11-12.toml")),
+    (
+        "10-11-12-polygon",
+        include_str!("../screens/10-11-12-polygon.toml"),
+    ),
+    ("high-yield", include_str!("../screens/high-yield.toml")),
+    (
+        "dividend-growth",
+        include_str!("../screens/dividend-growth.toml"),
+    ),
+];
+
+/// Dividend growth of recent 5 years should not be slower than of recent 10 years
+pub const MIN_DIV_GROWTH_5Y_TO_10Y_RATIO: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+}
+
+impl Comparison {
+    pub fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterOrEqual => value >= threshold,
+            Comparison::Less => value < threshold,
+            Comparison::LessOrEqual => value <= threshold,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operation {
+    fn apply(&self, left: f64, right: f64) -> Option<f64> {
+        match self {
+            Operation::Add => Some(left + right),
+            Operation::Subtract => Some(left - right),
+            Operation::Multiply => Some(left * right),
+            Operation::Divide if right != 0.0 => Some(left / right),
+            Operation::Divide => None,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operation::Add => "+",
+            Operation::Subtract => "-",
+            Operation::Multiply => "*",
+            Operation::Divide => "/",
+        }
+    }
+}
+
+/// Value that criterion is checked against: a column or an expression on two columns
+#[derive(Debug, Clone, PartialEq)]
+pub enum CriterionValue {
+    Column(String),
+    Expression(String, Operation, String),
+}
+
+impl CriterionValue {
+    pub fn evaluate(&self, df: &DataFrame) -> Vec<Option<f64>> {
+        match self {
+            CriterionValue::Column(name) => optional_f64_column(df, name),
+            CriterionValue::Expression(left, operation, right) => optional_f64_column(df, left)
+                .into_iter()
+                .zip(optional_f64_column(df, right))
+                .map(|(l, r)| match (l, r) {
+                    (Some(l), Some(r)) => operation.apply(l, r),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for CriterionValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CriterionValue::Column(name) => write!(f, "\"{name}\""),
+            CriterionValue::Expression(left, operation, right) => {
+                write!(f, "\"{left}\" {} \"{right}\"", operation.symbol())
+            }
+        }
+    }
+}
+
+/// Parse expression on two quoted column names e.g. `"Current Div" / "CF/Share"`
+pub fn parse_expression(expression: &str) -> Result<CriterionValue, &'static str> {
+    let tokens: Vec<&str> = expression.split('"').collect();
+    if tokens.len() != 5 || !tokens[0].trim().is_empty() || !tokens[4].trim().is_empty() {
+        log::error!("Unsupported expression: {expression}");
+        return Err("Error: Expression should be: \"<column>\" <operator> \"<column>\"");
+    }
+    let operation = match tokens[2].trim() {
+        "+" => Operation::Add,
+        "-" => Operation::Subtract,
+        "*" => Operation::Multiply,
+        "/" => Operation::Divide,
+        operator => {
+            log::error!("Unsupported operator \"{operator}\" in expression: {expression}");
+            return Err("Error: Unsupported operator in expression");
+        }
+    };
+    Ok(CriterionValue::Expression(
+        tokens[1].to_owned(),
+        operation,
+        tokens[3].to_owned(),
+    ))
+}
+
+/// Single screening criterion with the threshold it was evaluated with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Criterion {
+    pub name: String,
+    pub value: CriterionValue,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// What the threshold was derived from
+    pub derivation: String,
+}
+
+impl Criterion {
+    /// Failed criterion whose value is within margin[%] of the threshold
+    pub fn is_near_miss(&self, value: f64, margin: f64) -> bool {
+        !self.comparison.holds(value, self.threshold)
+            && (value - self.threshold).abs() <= (self.threshold * margin / 100.0).abs()
+    }
+}
+
+/// Threshold given as a number or as a name of command line parameter
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Threshold {
+    Value(f64),
+    Parameter(String),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    /// Column to compare with threshold. Either column or expression has to be given
+    pub column: Option<String>,
+    /// Expression on two columns e.g. `"Current Div" / "CF/Share"`
+    pub expression: Option<String>,
+    pub op: Comparison,
+    pub threshold: Threshold,
+    /// Rationale of the threshold
+    pub description: Option<String>,
+}
+
+/// Ordered list of rules that companies are screened with
+#[derive(Debug, Clone, Deserialize)]
+pub struct Screen {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Column that screened companies are sorted along (descending)
+    pub sort_by: Option<String>,
+    #[serde(rename = "rule")]
+    pub rules: Vec<Rule>,
+}
+
+/// Command line parameters that rule thresholds can refer to by name
+#[derive(Debug, Clone)]
+pub struct ScreenParameters {
+    pub sp500_divy: f64,
+    pub inflation: f64,
+    pub min_div_yield: f64,
+    pub max_div_yield: f64,
+    /// Maximum accepted Dividend Payout rate[%]
+    pub max_div_payout_rate: f64,
+    pub min_div_growth_rate: f64,
+}
+
+/// Minimal accepted Dividend Yield of 10-11-12 system and description of what it was derived from
+pub fn minimal_accepted_div_yield(sp500_divy: f64, inflation: f64, min_divy: f64) -> (f64, String) {
+    let min_ref_sp500 = sp500_divy * 1.5;
+    let (mut minimal_accepted_divy, mut derivation) = if min_ref_sp500 > inflation {
+        (
+            min_ref_sp500,
+            format!("1.5 x S&P500 Div Yield ({sp500_divy}%) is above inflation ({inflation}%)"),
+        )
+    } else {
+        (
+            inflation,
+            format!("Inflation ({inflation}%) is above 1.5 x S&P500 Div Yield ({sp500_divy}%)"),
+        )
+    };
+    if min_divy > minimal_accepted_divy {
+        derivation = format!(
+            "--min-div-yield ({min_divy}%) is above derived minimum ({minimal_accepted_divy:.2}%)"
+        );
+        minimal_accepted_divy = min_divy;
+    };
+    (minimal_accepted_divy, derivation)
+}
+
+impl ScreenParameters {
+    fn resolve(&self, parameter: &str) -> Result<(f64, String), &'static str> {
+        match parameter {
+            "min_div_yield" => Ok(minimal_accepted_div_yield(
+                self.sp500_divy,
+                self.inflation,
+                self.min_div_yield,
+            )),
+            "max_div_yield" => Ok((self.max_div_yield, "--max-div-yield".to_owned())),
+            "max_div_payout_rate" => Ok((
+                self.max_div_payout_rate / 100.0,
+                "--max-div-payout-rate".to_owned(),
+            )),
+            "max_div_payout_percent" => {
+                Ok((self.max_div_payout_rate, "--max-div-payout-rate".to_owned()))
+            }
+            "min_div_growth_rate" => {
+                Ok((self.min_div_growth_rate, "--min-div-growth-rate".to_owned()))
+            }
+            "inflation" => Ok((self.inflation, "--inflation".to_owned())),
+            "min_div_growth_5y_to_10y_ratio" => Ok((
+                MIN_DIV_GROWTH_5Y_TO_10Y_RATIO,
+                "Dividend growth should not slow down".to_owned(),
+            )),
+            _ => {
+                log::error!("Unknown threshold parameter: {parameter}");
+                Err("Error: Unknown threshold parameter in screening rule")
+            }
+        }
+    }
+}
+
+impl Screen {
+    pub fn from_toml(content: &str) -> Result<Self, &'static str> {
+        toml::from_str(content).map_err(|e| {
+            log::error!("Invalid screen definition: {e}");
+            "Error: Invalid screen definition"
+        })
+    }
+
+    /// Screen shipped with the project e.g. "10-11-12"
+    pub fn preset(name: &str) -> Result<Self, &'static str> {
+        let (_, content) = PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .ok_or_else(|| {
+                log::error!("Unknown preset: {name}");
+                "Error: Unknown screen preset"
+            })?;
+        Screen::from_toml(content)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, &'static str> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            log::error!("Unable to read {path}: {e}");
+            "Error: Unable to read screen file"
+        })?;
+        Screen::from_toml(&content)
+    }
+
+    /// Rules with thresholds resolved against command line parameters
+    pub fn criteria(&self, params: &ScreenParameters) -> Result<Vec<Criterion>, &'static str> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let value = match (&rule.column, &rule.expression) {
+                    (Some(column), None) => CriterionValue::Column(column.clone()),
+                    (None, Some(expression)) => parse_expression(expression)?,
+                    _ => {
+                        log::error!("Rule \"{}\" needs either column or expression", rule.name);
+                        return Err("Error: Rule needs either column or expression");
+                    }
+                };
+                let (threshold, derivation) = match &rule.threshold {
+                    Threshold::Value(threshold) => {
+                        (*threshold, format!("Fixed in \"{}\" screen", self.name))
+                    }
+                    Threshold::Parameter(parameter) => params.resolve(parameter)?,
+                };
+                Ok(Criterion {
+                    name: rule.name.clone(),
+                    value,
+                    comparison: rule.op,
+                    threshold,
+                    derivation: rule.description.clone().unwrap_or(derivation),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Keep companies meeting all criteria (applied in order). Companies with no data
+/// to check a criterion are rejected
+pub fn apply_criteria(
+    df: &DataFrame,
+    criteria: &[Criterion],
+    sort_by: Option<&str>,
+) -> Result<DataFrame, &'static str> {
+    let df = criteria.iter().try_fold(df.clone(), |df, criterion| {
+        let mask: BooleanChunked = criterion
+            .value
+            .evaluate(&df)
+            .into_iter()
+            .map(|value| {
+                value.map_or(false, |v| {
+                    criterion.comparison.holds(v, criterion.threshold)
+                })
+            })
+            .collect();
+        let df = df.filter(&mask).map_err(|_| "Error filtering")?;
+        log::info!("Shortlisted by {}: {df}", criterion.name);
+        Ok::<DataFrame, &'static str>(df)
+    })?;
+    match sort_by {
+        Some(column) => df.sort([column], true, false).map_err(|_| {
+            log::error!("Could not sort along {column}");
+            "Error: Could not sort screened companies"
+        }),
+        None => Ok(df),
+    }
+}
+
+/// Companies passing given rules of 10-11-12 preset
+fn screen_10_11_12(
+    df: &DataFrame,
+    params: &ScreenParameters,
+    rules: &[&str],
+    sort_by: Option<&str>,
+) -> Result<DataFrame, &'static str> {
+    let criteria: Vec<Criterion> = Screen::preset("10-11-12")?
+        .criteria(params)?
+        .into_iter()
+        .filter(|criterion| rules.contains(&criterion.name.as_str()))
+        .collect();
+    apply_criteria(df, &criteria, sort_by)
+}
+
+/// Companies whose Dividend Yield is within bounds of 10-11-12 system, sorted along Div Yield
+pub fn analyze_div_yield(
+    df: &DataFrame,
+    sp500_divy: f64,
+    inflation: f64,
+    min_divy: f64,
+    max_divy: f64,
+) -> Result<DataFrame, &'static str> {
+    let params = ScreenParameters {
+        sp500_divy,
+        inflation,
+        min_div_yield: min_divy,
+        max_div_yield: max_divy,
+        max_div_payout_rate: 100.0,
+        min_div_growth_rate: 0.0,
+    };
+    screen_10_11_12(
+        df,
+        &params,
+        &["Min Div Yield", "Max Div Yield"],
+        Some("Div Yield"),
+    )
+}
+
+/// Companies whose Dividend Payout rate (Current Div / CF/Share) is below given threshold
+/// (fraction e.g. 0.75), sorted along Div Yield
+pub fn analyze_dividend_payout_rate(
+    df: &DataFrame,
+    max_threshold: f64,
+) -> Result<DataFrame, &'static str> {
+    let params = ScreenParameters {
+        sp500_divy: 0.0,
+        inflation: 0.0,
+        min_div_yield: 0.0,
+        max_div_yield: 100.0,
+        max_div_payout_rate: max_threshold * 100.0,
+        min_div_growth_rate: 0.0,
+    };
+    screen_10_11_12(df, &params, &["Div Payout"], Some("Div Yield"))
+}
+
+/// Companies whose dividend growth does not slow down and grew at least given rate[%] last year
+pub fn analyze_div_growth(df: &DataFrame, min_growth_rate: f64) -> Result<DataFrame, &'static str> {
+    let params = ScreenParameters {
+        sp500_divy: 0.0,
+        inflation: 0.0,
+        min_div_yield: 0.0,
+        max_div_yield: 100.0,
+        max_div_payout_rate: 100.0,
+        min_div_growth_rate: min_growth_rate,
+    };
+    screen_10_11_12(df, &params, &["DGR 5Y to 10Y", "Min DGR 1Y"], None)
+}
+
+/// Get values of numeric column as f64, missing column is reported and treated as no data
+pub fn optional_f64_column(df: &DataFrame, name: &str) -> Vec<Option<f64>> {
+    match df.column(name).and_then(|c| {
+        c.cast(&DataType::Float64)?
+            .f64()
+            .map(|ca| ca.into_iter().collect::<Vec<Option<f64>>>())
+    }) {
+        Ok(values) => values,
+        Err(_) => {
+            log::warn!("\"{name}\" column is not available");
+            vec![None; df.height()]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> ScreenParameters {
+        ScreenParameters {
+            sp500_divy: 1.61,
+            inflation: 3.4,
+            min_div_yield: 4.7,
+            max_div_yield: 10.0,
+            max_div_payout_rate: 75.0,
+            min_div_growth_rate: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_minimal_accepted_div_yield() -> Result<(), String> {
+        let (divy, derivation) = minimal_accepted_div_yield(2.5, 3.4, 0.0);
+        assert_eq!(divy, 3.75);
+        assert!(derivation.starts_with("1.5 x S&P500"));
+        let (divy, derivation) = minimal_accepted_div_yield(1.61, 3.4, 0.0);
+        assert_eq!(divy, 3.4);
+        assert!(derivation.starts_with("Inflation"));
+        let (divy, derivation) = minimal_accepted_div_yield(1.61, 3.4, 4.7);
+        assert_eq!(divy, 4.7);
+        assert!(derivation.starts_with("--min-div-yield"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_expression() -> Result<(), String> {
+        assert_eq!(
+            parse_expression("\"Current Div\" / \"CF/Share\""),
+            Ok(CriterionValue::Expression(
+                "Current Div".to_owned(),
+                Operation::Divide,
+                "CF/Share".to_owned()
+            ))
+        );
+        assert!(parse_expression("\"DGR 5Y\" % \"DGR 10Y\"").is_err());
+        assert!(parse_expression("DGR 5Y / DGR 10Y").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_presets() -> Result<(), String> {
+        PRESETS.iter().try_for_each(|(name, _)| {
+            let screen = Screen::preset(name)?;
+            assert_eq!(screen.name, *name);
+            screen.criteria(&params()).map(|_| ())
+        })?;
+        assert!(Screen::preset("get-rich-quick").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ten_eleven_twelve_criteria() -> Result<(), String> {
+        let criteria = Screen::preset("10-11-12")?.criteria(&params())?;
+        let thresholds: Vec<f64> = criteria.iter().map(|c| c.threshold).collect();
+        assert_eq!(thresholds, vec![4.7, 10.0, 0.75, 1.0, 10.0]);
+        assert_eq!(
+            criteria[3].derivation,
+            "Dividend growth should not slow down"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ten_eleven_twelve_polygon_criteria() -> Result<(), String> {
+        let criteria = Screen::preset("10-11-12-polygon")?.criteria(&params())?;
+        let thresholds: Vec<f64> = criteria.iter().map(|c| c.threshold).collect();
+        assert_eq!(thresholds, vec![4.7, 10.0, 75.0, 10.0]);
+
+        let s1 = Series::new("Symbol", &["ABR", "O", "MAIN"]);
+        let s2 = Series::new("Div Yield[%]", &[Some(11.5), Some(5.5), Some(6.2)]);
+        let s3 = Series::new("Payout ratio[%]", &[Some(60.0), Some(70.0), None]);
+        let s4 = Series::new("DGR5G[%]", &[Some(15.0), Some(10.0), Some(12.0)]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4]).unwrap();
+
+        // ABR: yield above 10%, MAIN: no payout ratio data
+        let result = apply_criteria(&df, &criteria, None).unwrap();
+        let symbols = result.column("Symbol").unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols.get(0).unwrap(), AnyValue::Utf8("O"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_screen_from_toml() -> Result<(), String> {
+        let screen = Screen::from_toml(
+            r#"
+            name = "custom"
+            [[rule]]
+            name = "Yield"
+            column = "Div Yield"
+            op = ">="
+            threshold = "sp500_divy"
+            "#,
+        )?;
+        assert!(screen.criteria(&params()).is_err());
+
+        let screen = Screen::from_toml(
+            r#"
+            name = "custom"
+            [[rule]]
+            name = "Yield"
+            op = ">="
+            threshold = 4
+            "#,
+        )?;
+        assert!(screen.criteria(&params()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_criteria() -> Result<(), String> {
+        let criteria = Screen::preset("10-11-12")?.criteria(&params())?;
+
+        let s1 = Series::new("Symbol", &["ABM", "INTC", "CAT", "XOM"]);
+        let s2 = Series::new("Div Yield", &[5.54, 1.32, 5.0, 6.0]);
+        let s3 = Series::new("Current Div", &[0.22, 0.365, 0.2, 0.5]);
+        let s4 = Series::new("CF/Share", &[1.7, 1.5, 2.0, 2.0]);
+        let s5 = Series::new("DGR 1Y", &[12.0, 10.0, 15.0, 11.0]);
+        let s6 = Series::new("DGR 5Y", &[6.0, 5.0, 3.0, 2.0]);
+        let s7 = Series::new("DGR 10Y", &[Some(5.0), Some(10.0), Some(2.0), None]);
+
+        let df: DataFrame = DataFrame::new(vec![s1, s2, s3, s4, s5, s6, s7]).unwrap();
+
+        // INTC: too low yield, XOM: no DGR 10Y data
+        let result = apply_criteria(&df, &criteria, Some("DGR 1Y")).unwrap();
+        let symbols = result.column("Symbol").unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols.get(0).unwrap(), AnyValue::Utf8("CAT"));
+        assert_eq!(symbols.get(1).unwrap(), AnyValue::Utf8("ABM"));
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+use std::fmt;
+
+/// Sectors of a stable taxonomy (modelled after GICS) shared by Polygon.io SIC
+/// classification and sectors of the dividend lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sector {
+    Energy,
+    Materials,
+    Industrials,
+    ConsumerDiscretionary,
+    ConsumerStaples,
+    HealthCare,
+    Financials,
+    InformationTechnology,
+    CommunicationServices,
+    Utilities,
+    RealEstate,
+    Unknown,
+}
+
+impl fmt::Display for Sector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Sector::Energy => "Energy",
+            Sector::Materials => "Materials",
+            Sector::Industrials => "Industrials",
+            Sector::ConsumerDiscretionary => "Consumer Discretionary",
+            Sector::ConsumerStaples => "Consumer Staples",
+            Sector::HealthCare => "Health Care",
+            Sector::Financials => "Financials",
+            Sector::InformationTechnology => "Information Technology",
+            Sector::CommunicationServices => "Communication Services",
+            Sector::Utilities => "Utilities",
+            Sector::RealEstate => "Real Estate",
+            Sector::Unknown => "Unknown",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// SIC code ranges (inclusive) mapped into sector and industry
+const SIC_TAXONOMY: [(u32, u32, Sector, &str); 61] = [
+    (100, 999, Sector::ConsumerStaples, "Agricultural Products"),
+    (1000, 1099, Sector::Materials, "Metals & Mining"),
+    (1200, 1299, Sector::Energy, "Coal"),
+    (1300, 1399, Sector::Energy, "Oil & Gas"),
+    (1400, 1499, Sector::Materials, "Construction Materials"),
+    (
+        1500,
+        1799,
+        Sector::Industrials,
+        "Construction & Engineering",
+    ),
+    (2000, 2099, Sector::ConsumerStaples, "Food & Beverages"),
+    (2100, 2199, Sector::ConsumerStaples, "Tobacco"),
+    (
+        2200,
+        2399,
+        Sector::ConsumerDiscretionary,
+        "Textiles & Apparel",
+    ),
+    (2400, 2499, Sector::Materials, "Paper & Forest Products"),
+    (
+        2500,
+        2599,
+        Sector::ConsumerDiscretionary,
+        "Household Durables",
+    ),
+    (2600, 2699, Sector::Materials, "Paper & Forest Products"),
+    (2700, 2799, Sector::CommunicationServices, "Media"),
+    (2800, 2829, Sector::Materials, "Chemicals"),
+    (
+        2830,
+        2839,
+        Sector::HealthCare,
+        "Pharmaceuticals & Biotechnology",
+    ),
+    (
+        2840,
+        2844,
+        Sector::ConsumerStaples,
+        "Household & Personal Products",
+    ),
+    (2845, 2899, Sector::Materials, "Chemicals"),
+    (2900, 2999, Sector::Energy, "Oil & Gas"),
+    (3000, 3099, Sector::Materials, "Chemicals"),
+    (
+        3100,
+        3199,
+        Sector::ConsumerDiscretionary,
+        "Textiles & Apparel",
+    ),
+    (3200, 3299, Sector::Materials, "Construction Materials"),
+    (3300, 3399, Sector::Materials, "Metals & Mining"),
+    (3400, 3499, Sector::Industrials, "Machinery"),
+    (3500, 3569, Sector::Industrials, "Machinery"),
+    (
+        3570,
+        3579,
+        Sector::InformationTechnology,
+        "Technology Hardware",
+    ),
+    (3580, 3599, Sector::Industrials, "Machinery"),
+    (3600, 3659, Sector::Industrials, "Electrical Equipment"),
+    (
+        3660,
+        3669,
+        Sector::InformationTechnology,
+        "Communications Equipment",
+    ),
+    (3670, 3679, Sector::InformationTechnology, "Semiconductors"),
+    (3680, 3699, Sector::Industrials, "Electrical Equipment"),
+    (3700, 3719, Sector::ConsumerDiscretionary, "Automobiles"),
+    (3720, 3799, Sector::Industrials, "Aerospace & Defense"),
+    (
+        3800,
+        3839,
+        Sector::InformationTechnology,
+        "Electronic Instruments",
+    ),
+    (3840, 3859, Sector::HealthCare, "Health Care Equipment"),
+    (
+        3860,
+        3999,
+        Sector::ConsumerDiscretionary,
+        "Leisure Products",
+    ),
+    (4000, 4799, Sector::Industrials, "Transportation"),
+    (
+        4800,
+        4899,
+        Sector::CommunicationServices,
+        "Telecommunication Services",
+    ),
+    (4900, 4949, Sector::Utilities, "Utilities"),
+    (4950, 4959, Sector::Industrials, "Commercial Services"),
+    (4960, 4999, Sector::Utilities, "Utilities"),
+    (
+        5000,
+        5199,
+        Sector::Industrials,
+        "Trading Companies & Distributors",
+    ),
+    (5200, 5399, Sector::ConsumerDiscretionary, "Retail"),
+    (
+        5400,
+        5499,
+        Sector::ConsumerStaples,
+        "Food & Staples Retailing",
+    ),
+    (5500, 5799, Sector::ConsumerDiscretionary, "Retail"),
+    (5800, 5899, Sector::ConsumerDiscretionary, "Restaurants"),
+    (5900, 5999, Sector::ConsumerDiscretionary, "Retail"),
+    (6000, 6199, Sector::Financials, "Banks"),
+    (6200, 6299, Sector::Financials, "Capital Markets"),
+    (6300, 6499, Sector::Financials, "Insurance"),
+    (6500, 6599, Sector::RealEstate, "Real Estate Management"),
+    (6700, 6797, Sector::Financials, "Diversified Financials"),
+    (6798, 6798, Sector::RealEstate, "REITs"),
+    (6799, 6799, Sector::Financials, "Diversified Financials"),
+    (
+        7000,
+        7099,
+        Sector::ConsumerDiscretionary,
+        "Hotels & Leisure",
+    ),
+    (
+        7200,
+        7299,
+        Sector::ConsumerDiscretionary,
+        "Consumer Services",
+    ),
+    (7300, 7369, Sector::Industrials, "Commercial Services"),
+    (
+        7370,
+        7379,
+        Sector::InformationTechnology,
+        "Software & Services",
+    ),
+    (7380, 7699, Sector::Industrials, "Commercial Services"),
+    (7800, 7999, Sector::CommunicationServices, "Entertainment"),
+    (
+        8000,
+        8099,
+        Sector::HealthCare,
+        "Health Care Providers & Services",
+    ),
+    (8100, 8999, Sector::Industrials, "Professional Services"),
+];
+
+/// Sector names and their aliases as found in dividend lists and other data providers
+const SECTOR_ALIASES: [(&str, Sector); 22] = [
+    ("energy", Sector::Energy),
+    ("materials", Sector::Materials),
+    ("basic materials", Sector::Materials),
+    ("industrials", Sector::Industrials),
+    ("consumer discretionary", Sector::ConsumerDiscretionary),
+    ("consumer cyclical", Sector::ConsumerDiscretionary),
+    ("consumer staples", Sector::ConsumerStaples),
+    ("consumer defensive", Sector::ConsumerStaples),
+    ("health care", Sector::HealthCare),
+    ("healthcare", Sector::HealthCare),
+    ("financials", Sector::Financials),
+    ("financial services", Sector::Financials),
+    ("information technology", Sector::InformationTechnology),
+    ("technology", Sector::InformationTechnology),
+    ("communication services", Sector::CommunicationServices),
+    ("communications", Sector::CommunicationServices),
+    ("telecommunication services", Sector::CommunicationServices),
+    ("utilities", Sector::Utilities),
+    ("real estate", Sector::RealEstate),
+    ("reit", Sector::RealEstate),
+    ("reits", Sector::RealEstate),
+    ("unknown", Sector::Unknown),
+];
+
+/// Keywords of SIC descriptions used when SIC code is not available. More specific
+/// keywords come first
+const SIC_DESCRIPTION_KEYWORDS: [(&str, Sector); 16] = [
+    ("real estate investment trust", Sector::RealEstate),
+    ("real estate", Sector::RealEstate),
+    ("pharmaceutical", Sector::HealthCare),
+    ("medical", Sector::HealthCare),
+    ("bank", Sector::Financials),
+    ("insurance", Sector::Financials),
+    ("investment", Sector::Financials),
+    ("petroleum", Sector::Energy),
+    ("crude", Sector::Energy),
+    ("electric services", Sector::Utilities),
+    ("gas", Sector::Utilities),
+    ("software", Sector::InformationTechnology),
+    ("semiconductor", Sector::InformationTechnology),
+    ("telephone", Sector::CommunicationServices),
+    ("retail", Sector::ConsumerDiscretionary),
+    ("food", Sector::ConsumerStaples),
+];
+
+impl Sector {
+    /// Parse sector name (case insensitive) as used in dividend lists and command line
+    pub fn from_name(name: &str) -> Option<Sector> {
+        let name = name.trim().to_lowercase();
+        SECTOR_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map(|(_, sector)| *sector)
+    }
+}
+
+/// Sector and industry of given SIC code e.g. "6798"
+pub fn classify_sic_code(sic_code: &str) -> Option<(Sector, &'static str)> {
+    let code = sic_code.trim().parse::<u32>().ok()?;
+    SIC_TAXONOMY
+        .iter()
+        .find(|(start, end, _, _)| (*start..=*end).contains(&code))
+        .map(|(_, _, sector, industry)| (*sector, *industry))
+}
+
+/// Sector and industry of Polygon.io company based on SIC code, or SIC description
+/// keywords when there is no code. Industry falls back to SIC description
+pub fn classify_sic(
+    sic_code: Option<&str>,
+    sic_description: Option<&str>,
+) -> (Sector, Option<String>) {
+    if let Some((sector, industry)) = sic_code.and_then(classify_sic_code) {
+        return (sector, Some(industry.to_owned()));
+    }
+    match sic_description {
+        Some(description) => {
+            let lowercase = description.to_lowercase();
+            let sector = SIC_DESCRIPTION_KEYWORDS
+                .iter()
+                .find(|(keyword, _)| lowercase.contains(keyword))
+                .map_or(Sector::Unknown, |(_, sector)| *sector);
+            log::info!("No SIC code, {description} classified as {sector}");
+            (sector, Some(description.to_owned()))
+        }
+        None => (Sector::Unknown, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sic_taxonomy_is_ordered() -> Result<(), String> {
+        SIC_TAXONOMY.windows(2).try_for_each(|w| {
+            if w[0].1 < w[1].0 && w[0].0 <= w[0].1 {
+                Ok(())
+            } else {
+                Err(format!("Overlapping SIC ranges: {:?} {:?}", w[0], w[1]))
+            }
+        })
+    }
+
+    #[test]
+    fn test_classify_sic_code() -> Result<(), String> {
+        assert_eq!(
+            classify_sic_code("6798"),
+            Some((Sector::RealEstate, "REITs"))
+        );
+        assert_eq!(
+            classify_sic_code("2834").map(|x| x.0),
+            Some(Sector::HealthCare)
+        );
+        assert_eq!(
+            classify_sic_code("4911").map(|x| x.0),
+            Some(Sector::Utilities)
+        );
+        assert_eq!(classify_sic_code("9999"), None);
+        assert_eq!(classify_sic_code("N/A"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_classify_sic() -> Result<(), String> {
+        assert_eq!(
+            classify_sic(Some("7372"), Some("SERVICES-PREPACKAGED SOFTWARE")),
+            (
+                Sector::InformationTechnology,
+                Some("Software & Services".to_owned())
+            )
+        );
+        assert_eq!(
+            classify_sic(None, Some("REAL ESTATE INVESTMENT TRUSTS")),
+            (
+                Sector::RealEstate,
+                Some("REAL ESTATE INVESTMENT TRUSTS".to_owned())
+            )
+        );
+        assert_eq!(classify_sic(None, None), (Sector::Unknown, None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sector_from_name() -> Result<(), String> {
+        assert_eq!(
+            Sector::from_name("Consumer Staples"),
+            Some(Sector::ConsumerStaples)
+        );
+        assert_eq!(
+            Sector::from_name(" technology "),
+            Some(Sector::InformationTechnology)
+        );
+        assert_eq!(Sector::from_name("REIT"), Some(Sector::RealEstate));
+        assert_eq!(Sector::from_name("Crypto"), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+/// Dividend withholding tax[%] of company domicile: (country code, statutory rate, treaty rate).
+/// Statutory rate is withheld unless treaty rate is applied at source or withholding above it is
+/// refunded (W-8BEN form for US companies)
+const DOMICILES: &[(&str, f64, f64)] = &[
+    ("US", 30.0, 15.0),
+    ("CA", 25.0, 15.0),
+    ("UK", 0.0, 0.0),
+    ("IE", 25.0, 15.0),
+    ("NL", 15.0, 15.0),
+    ("DE", 26.375, 15.0),
+    ("FR", 25.0, 15.0),
+    ("CH", 35.0, 15.0),
+    ("PL", 19.0, 19.0),
+];
+
+/// Tax[%] on dividends of investor's residence. Foreign tax withheld is credited up to treaty
+/// rate and the rest is paid as top-up
+const RESIDENCES: &[(&str, f64)] = &[("PL", 19.0), ("US", 15.0), ("UK", 8.75), ("DE", 26.375)];
+
+/// United Kingdom may be given by its ISO code as well
+fn country_code(code: &str) -> String {
+    match code.trim().to_uppercase().as_str() {
+        "GB" => "UK".to_owned(),
+        code => code.to_owned(),
+    }
+}
+
+/// Tax of dividends of a company as fractions of gross dividend
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DividendTax {
+    /// Withheld by domicile of company
+    pub withholding: f64,
+    /// Paid in residence of investor on top of withholding tax
+    pub top_up: f64,
+    /// Part of withholding tax credited against tax of residence
+    pub credit: f64,
+}
+
+impl DividendTax {
+    pub fn total(&self) -> f64 {
+        self.withholding + self.top_up
+    }
+
+    /// Withholding tax which is not credited against tax of residence
+    pub fn leakage(&self) -> f64 {
+        self.withholding - self.credit
+    }
+}
+
+impl fmt::Display for DividendTax {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Tax[%]: {:.2} withheld + {:.2} top-up",
+            self.withholding * 100.0,
+            self.top_up * 100.0
+        )
+    }
+}
+
+/// Dividend taxation of investor of given residence
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaxModel {
+    residence: String,
+    residence_rate: f64,
+    /// W-8BEN form is filed so treaty rate is withheld by US companies
+    pub w8ben: bool,
+    /// Treaty rate is applied at source or withholding above it is refunded by domiciles other
+    /// than US
+    pub treaty_relief: bool,
+}
+
+impl TaxModel {
+    pub fn new(
+        residence: &str,
+        w8ben: bool,
+        treaty_relief: bool,
+    ) -> Result<TaxModel, &'static str> {
+        let residence = country_code(residence);
+        let residence_rate = RESIDENCES
+            .iter()
+            .find(|(code, _)| *code == residence)
+            .map(|(_, rate)| rate / 100.0)
+            .ok_or_else(|| {
+                log::error!("Unsupported residence: {residence}");
+                "Error: Unsupported residence. Possible values: PL, US, UK, DE"
+            })?;
+        Ok(TaxModel {
+            residence,
+            residence_rate,
+            w8ben,
+            treaty_relief,
+        })
+    }
+
+    /// Tax of dividends of company domiciled in given country
+    pub fn dividend_tax(&self, domicile: &str) -> Result<DividendTax, &'static str> {
+        let domicile = country_code(domicile);
+        // Domestic dividends are taxed at source with nothing left to pay on top
+        if domicile == self.residence {
+            return Ok(DividendTax {
+                withholding: self.residence_rate,
+                top_up: 0.0,
+                credit: self.residence_rate,
+            });
+        }
+        let (_, statutory, treaty) = DOMICILES
+            .iter()
+            .find(|(code, _, _)| *code == domicile)
+            .ok_or_else(|| {
+                log::error!("Unsupported domicile: {domicile}");
+                "Error: Unsupported domicile. Possible values: US, CA, UK, IE, NL, DE, FR, CH, PL"
+            })?;
+        let is_relieved = if domicile == "US" {
+            self.w8ben
+        } else {
+            self.treaty_relief
+        };
+        let withholding = if is_relieved { treaty } else { statutory } / 100.0;
+        // Withholding above treaty rate or tax of residence is not credited
+        let credit = withholding.min(treaty / 100.0).min(self.residence_rate);
+        Ok(DividendTax {
+            withholding,
+            top_up: self.residence_rate - credit,
+            credit,
+        })
+    }
+}
+
+/// Tax not credited against tax of residence (tax leakage) on dividends which are net (after
+/// tax) of given tax
+pub fn tax_leakage(net_dividends: f64, tax: &DividendTax) -> f64 {
+    if tax.total() >= 1.0 {
+        0.0
+    } else {
+        net_dividends * tax.leakage() / (1.0 - tax.total())
+    }
+}
+
+/// Tax paid on dividends which are net (after tax) of given tax rate
+pub fn tax_paid(net_dividends: f64, tax_rate: f64) -> f64 {
+    if tax_rate >= 1.0 {
+        0.0
+    } else {
+        net_dividends * tax_rate / (1.0 - tax_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rounded(tax: DividendTax) -> (f64, f64) {
+        (
+            (tax.withholding * 10000.0).round() / 100.0,
+            (tax.top_up * 10000.0).round() / 100.0,
+        )
+    }
+
+    #[test]
+    fn test_polish_resident() -> Result<(), String> {
+        let model = TaxModel::new("pl", true, true)?;
+        assert_eq!(rounded(model.dividend_tax("US")?), (15.0, 4.0));
+        assert_eq!(rounded(model.dividend_tax("CA")?), (15.0, 4.0));
+        assert_eq!(rounded(model.dividend_tax("CH")?), (15.0, 4.0));
+        assert_eq!(rounded(model.dividend_tax("GB")?), (0.0, 19.0));
+        assert_eq!(rounded(model.dividend_tax("PL")?), (19.0, 0.0));
+        // Withholding above treaty rate is a loss: only 15% of 35% withheld in CH is credited
+        // against 19% of PL, so 39% is paid in total
+        let model = TaxModel::new("PL", false, false)?;
+        assert_eq!(rounded(model.dividend_tax("US")?), (30.0, 4.0));
+        assert_eq!(rounded(model.dividend_tax("CH")?), (35.0, 4.0));
+        assert_eq!(rounded(model.dividend_tax("CA")?), (25.0, 4.0));
+        // Statutory rate not above treaty rate is withheld anyway
+        assert_eq!(rounded(model.dividend_tax("NL")?), (15.0, 4.0));
+        assert!(model.dividend_tax("XX").is_err());
+        assert!(TaxModel::new("XX", true, true).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tax_paid() -> Result<(), String> {
+        let tax = TaxModel::new("PL", true, false)?.dividend_tax("US")?;
+        // 100$ gross dividend, 19$ of tax
+        assert_eq!((tax_paid(81.0, tax.total()) * 100.0).round() / 100.0, 19.0);
+        assert_eq!(tax_paid(81.0, 0.0), 0.0);
+        // Treaty rate withheld is credited in full
+        assert_eq!(tax_leakage(81.0, &tax), 0.0);
+        // 100$ gross dividend, 20$ of 35$ withheld in CH is not credited
+        let tax = TaxModel::new("PL", true, false)?.dividend_tax("CH")?;
+        assert_eq!((tax_leakage(61.0, &tax) * 100.0).round() / 100.0, 20.0);
+        // Withholding above tax of residence is not credited: 15% of US against 8.75% of UK
+        let tax = TaxModel::new("UK", true, false)?.dividend_tax("US")?;
+        assert_eq!(rounded(tax), (15.0, 0.0));
+        assert_eq!((tax_leakage(85.0, &tax) * 100.0).round() / 100.0, 6.25);
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Dividend data of watched company as seen on a given run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub symbol: String,
+    pub price: Option<f64>,
+    /// Most recent dividend payment
+    pub curr_div: Option<f64>,
+    /// Dividend Yield[%]
+    pub div_yield: Option<f64>,
+    /// Dividend growth rate[%]
+    pub dgr: Option<f64>,
+    /// Dividend Payout rate[%]
+    pub payout_ratio: Option<f64>,
+    /// Years of consecutive dividend growth
+    pub streak: Option<u32>,
+}
+
+/// Named list of watched tickers with their snapshots from last run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Watchlist {
+    pub name: String,
+    /// Date of last refresh
+    pub updated: Option<String>,
+    #[serde(default)]
+    pub tickers: Vec<String>,
+    #[serde(default, rename = "snapshot")]
+    pub snapshots: Vec<Snapshot>,
+}
+
+/// Thresholds whose crossing is reported as a change
+#[derive(Debug, Clone)]
+pub struct ChangeThresholds {
+    /// Minimum accepted Dividend Yield[%]
+    pub min_div_yield: f64,
+    /// Maximum accepted Dividend Payout rate[%]
+    pub max_div_payout_rate: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    NewTicker,
+    DividendRaise { from: f64, to: f64 },
+    DividendCut { from: f64, to: f64 },
+    YieldAboveMinimum { yield_: f64, threshold: f64 },
+    YieldBelowMinimum { yield_: f64, threshold: f64 },
+    PayoutAboveMaximum { payout: f64, threshold: f64 },
+    PayoutBelowMaximum { payout: f64, threshold: f64 },
+    StreakBroken { from: u32, to: u32 },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::NewTicker => write!(f, "Added to watchlist"),
+            Change::DividendRaise { from, to } => write!(
+                f,
+                "Dividend raised from {from} to {to} ({:+.2}%)",
+                (to / from - 1.0) * 100.0
+            ),
+            Change::DividendCut { from, to } => write!(
+                f,
+                "Dividend cut from {from} to {to} ({:+.2}%)",
+                (to / from - 1.0) * 100.0
+            ),
+            Change::YieldAboveMinimum { yield_, threshold } => {
+                write!(f, "Div Yield {yield_:.2}% rose above minimum {threshold}%")
+            }
+            Change::YieldBelowMinimum { yield_, threshold } => {
+                write!(f, "Div Yield {yield_:.2}% fell below minimum {threshold}%")
+            }
+            Change::PayoutAboveMaximum { payout, threshold } => {
+                write!(f, "Payout {payout:.2}% rose above maximum {threshold}%")
+            }
+            Change::PayoutBelowMaximum { payout, threshold } => {
+                write!(f, "Payout {payout:.2}% fell below maximum {threshold}%")
+            }
+            Change::StreakBroken { from, to } => {
+                write!(f, "Dividend growth streak broken ({from} -> {to} years)")
+            }
+        }
+    }
+}
+
+impl Watchlist {
+    fn path(dir: &str, name: &str) -> PathBuf {
+        Path::new(dir).join(format!("{name}.toml"))
+    }
+
+    /// Load watchlist from given directory. Not existing watchlist is created empty
+    pub fn load(dir: &str, name: &str) -> Result<Self, &'static str> {
+        let path = Watchlist::path(dir, name);
+        if !path.exists() {
+            log::info!(
+                "Watchlist {} does not exist. Creating new one",
+                path.display()
+            );
+            return Ok(Watchlist {
+                name: name.to_owned(),
+                ..Default::default()
+            });
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            log::error!("Unable to read {}: {e}", path.display());
+            "Error: Unable to read watchlist"
+        })?;
+        toml::from_str(&content).map_err(|e| {
+            log::error!("Invalid watchlist {}: {e}", path.display());
+            "Error: Invalid watchlist"
+        })
+    }
+
+    pub fn save(&self, dir: &str) -> Result<(), &'static str> {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            log::error!("Unable to create {dir}: {e}");
+            "Error: Unable to create watchlist directory"
+        })?;
+        let content = toml::to_string(self).map_err(|e| {
+            log::error!("Unable to serialize watchlist {}: {e}", self.name);
+            "Error: Unable to serialize watchlist"
+        })?;
+        let path = Watchlist::path(dir, &self.name);
+        std::fs::write(&path, content).map_err(|e| {
+            log::error!("Unable to write {}: {e}", path.display());
+            "Error: Unable to write watchlist"
+        })
+    }
+
+    /// Add tickers that are not watched yet
+    pub fn add(&mut self, tickers: &[String]) {
+        tickers.iter().for_each(|ticker| {
+            if !self.tickers.contains(ticker) {
+                self.tickers.push(ticker.clone());
+            }
+        });
+    }
+
+    pub fn snapshot(&self, symbol: &str) -> Option<&Snapshot> {
+        self.snapshots.iter().find(|s| s.symbol == symbol)
+    }
+}
+
+/// Changes of watched company since previous snapshot
+pub fn detect_changes(
+    previous: Option<&Snapshot>,
+    current: &Snapshot,
+    thresholds: &ChangeThresholds,
+) -> Vec<Change> {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return vec![Change::NewTicker],
+    };
+    let mut changes = vec![];
+
+    if let (Some(from), Some(to)) = (previous.curr_div, current.curr_div) {
+        if to > from {
+            changes.push(Change::DividendRaise { from, to });
+        } else if to < from {
+            changes.push(Change::DividendCut { from, to });
+        }
+    }
+
+    if let (Some(before), Some(yield_)) = (previous.div_yield, current.div_yield) {
+        let threshold = thresholds.min_div_yield;
+        if before < threshold && yield_ >= threshold {
+            changes.push(Change::YieldAboveMinimum { yield_, threshold });
+        } else if before >= threshold && yield_ < threshold {
+            changes.push(Change::YieldBelowMinimum { yield_, threshold });
+        }
+    }
+
+    if let (Some(before), Some(payout)) = (previous.payout_ratio, current.payout_ratio) {
+        let threshold = thresholds.max_div_payout_rate;
+        if before <= threshold && payout > threshold {
+            changes.push(Change::PayoutAboveMaximum { payout, threshold });
+        } else if before > threshold && payout <= threshold {
+            changes.push(Change::PayoutBelowMaximum { payout, threshold });
+        }
+    }
+
+    if let (Some(from), Some(to)) = (previous.streak, current.streak) {
+        if to < from {
+            changes.push(Change::StreakBroken { from, to });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thresholds() -> ChangeThresholds {
+        ChangeThresholds {
+            min_div_yield: 4.7,
+            max_div_payout_rate: 75.0,
+        }
+    }
+
+    #[test]
+    fn test_detect_changes() -> Result<(), String> {
+        let previous = Snapshot {
+            symbol: "ABR".to_owned(),
+            price: Some(14.0),
+            curr_div: Some(0.42),
+            div_yield: Some(4.5),
+            dgr: Some(15.0),
+            payout_ratio: Some(70.0),
+            streak: Some(12),
+        };
+        let current = Snapshot {
+            price: Some(13.0),
+            curr_div: Some(0.43),
+            div_yield: Some(5.0),
+            payout_ratio: Some(80.0),
+            streak: Some(0),
+            ..previous.clone()
+        };
+        assert_eq!(
+            detect_changes(Some(&previous), &current, &thresholds()),
+            vec![
+                Change::DividendRaise {
+                    from: 0.42,
+                    to: 0.43
+                },
+                Change::YieldAboveMinimum {
+                    yield_: 5.0,
+                    threshold: 4.7
+                },
+                Change::PayoutAboveMaximum {
+                    payout: 80.0,
+                    threshold: 75.0
+                },
+                Change::StreakBroken { from: 12, to: 0 },
+            ]
+        );
+        assert_eq!(
+            detect_changes(Some(&previous), &previous, &thresholds()),
+            vec![]
+        );
+        assert_eq!(
+            detect_changes(None, &current, &thresholds()),
+            vec![Change::NewTicker]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_dividend_cut() -> Result<(), String> {
+        let previous = Snapshot {
+            symbol: "MMM".to_owned(),
+            curr_div: Some(1.51),
+            div_yield: Some(6.0),
+            ..Default::default()
+        };
+        let current = Snapshot {
+            curr_div: Some(0.7),
+            div_yield: Some(2.8),
+            // No data is not a change
+            streak: None,
+            ..previous.clone()
+        };
+        let changes = detect_changes(Some(&previous), &current, &thresholds());
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0].to_string(),
+            "Dividend cut from 1.51 to 0.7 (-53.64%)"
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "Div Yield 2.80% fell below minimum 4.7%"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_watchlist_roundtrip() -> Result<(), String> {
+        let dir = crate::TempPath::new("watchlist");
+        let dir = dir.as_str();
+        let mut watchlist = Watchlist::load(dir, "core")?;
+        watchlist.add(&["ABR".to_owned(), "O".to_owned(), "ABR".to_owned()]);
+        watchlist.updated = Some("2024-01-02".to_owned());
+        watchlist.snapshots = vec![Snapshot {
+            symbol: "ABR".to_owned(),
+            curr_div: Some(0.43),
+            streak: Some(12),
+            ..Default::default()
+        }];
+        watchlist.save(dir)?;
+
+        let loaded = Watchlist::load(dir, "core")?;
+        assert_eq!(loaded.tickers, vec!["ABR".to_owned(), "O".to_owned()]);
+        assert_eq!(loaded.snapshot("ABR"), watchlist.snapshot("ABR"));
+        assert_eq!(loaded.snapshot("O"), None);
+        Ok(())
+    }
+}